@@ -1,58 +1,384 @@
+use aho_corasick::automaton::Automaton;
+use aho_corasick::{dfa, AhoCorasick, Anchored, MatchKind};
 use fancy_regex::Regex;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::time::Instant;
+
+// digit words of the built-in languages, indexed by their value
+const ENGLISH: [&str; 10] = [
+    "zero", "one", "two", "three", "four", "five", "six", "seven", "eight", "nine",
+];
+const FRENCH: [&str; 10] = [
+    "zéro", "un", "deux", "trois", "quatre", "cinq", "six", "sept", "huit", "neuf",
+];
+const GERMAN: [&str; 10] = [
+    "null", "eins", "zwei", "drei", "vier", "fünf", "sechs", "sieben", "acht", "neun",
+];
 
 fn main() {
     let contents = include_str!("../input");
 
-    let re1 = r"(?<digit>[1-9])";
-    let calib_sum1: i32 = contents.lines().map(|w| compute_calibration(re1, w)).sum();
-    println!("The first sum of calibrations is {calib_sum1}");
-
-    let re2 = r"(?=(?<digit>[1-9]|one|two|three|four|five|six|seven|eight|nine))";
-    let calib_sum2: i32 = contents.lines().map(|w| compute_calibration(re2, w)).sum();
-    println!("The second sum of calibrations is {calib_sum2}");
-}
-
-fn compute_calibration(re_str: &str, word: &str) -> i32 {
-    let re = Regex::new(re_str).unwrap();
-    let mut matches = re.captures_iter(word);
-    let mut calib = String::new();
-
-    // check first math
-    calib.push_str(match matches.next() {
-        None => return 0,
-        Some(cap) => match cap.unwrap().name("digit").unwrap().as_str() {
-            "1" | "one" => "1",
-            "2" | "two" => "2",
-            "3" | "three" => "3",
-            "4" | "four" => "4",
-            "5" | "five" => "5",
-            "6" | "six" => "6",
-            "7" | "seven" => "7",
-            "8" | "eight" => "8",
-            "9" | "nine" => "9",
-            _ => panic!("Unexpected first match."),
-        },
-    });
+    // the words of the second part are given by `--lang en,fr,de` and/or
+    // `--vocab <path>`, a file of `word digit` lines; `--zero` also accepts 0;
+    // `--strict` fails on lines without any digit and `--summary` counts the
+    // lines of each kind
+    let args: Vec<String> = std::env::args().collect();
+    let arg_value = |name: &str| {
+        let i = args.iter().position(|a| a == name)?;
+        Some(args.get(i + 1).unwrap_or_else(|| {
+            eprintln!("Missing value for {name}");
+            std::process::exit(1);
+        }))
+    };
+    let zero = args.iter().any(|a| a == "--zero");
+    let strict = args.iter().any(|a| a == "--strict");
+    let summary = args.iter().any(|a| a == "--summary");
+
+    let digits = Vocabulary::digits(zero);
+    let mut vocab = digits.clone();
+    let langs = arg_value("--lang").map_or("en", |l| l.as_str());
+    for lang in langs.split(',') {
+        let words = language(lang).unwrap_or_else(|| {
+            eprintln!("Unknown language {lang}, expected one of en, fr, de");
+            std::process::exit(1);
+        });
+        vocab.add_words(words, zero);
+    }
+    if let Some(path) = arg_value("--vocab") {
+        let table = std::fs::read_to_string(path).unwrap_or_else(|e| {
+            eprintln!("Cannot read {path}: {e}");
+            std::process::exit(1);
+        });
+        vocab.add_table(&table).unwrap_or_else(|e| {
+            eprintln!("Invalid vocabulary {path}: {e}");
+            std::process::exit(1);
+        });
+    }
+
+    // `day1 bench [megabytes]` times the scanner against the regex on a
+    // generated input
+    if args.get(1).map(|a| a.as_str()) == Some("bench") {
+        let size = args.get(2).and_then(|a| a.parse().ok()).unwrap_or(4);
+        bench(&vocab, size);
+        return;
+    }
+
+    let parts = [("first", &digits), ("second", &vocab)];
+    for (part, vocab) in parts {
+        let scanner = Scanner::new(vocab);
+        let calibs: Vec<Calibration> = contents.lines().map(|w| calibrate(&scanner, w)).collect();
+        if strict {
+            let missing: Vec<String> = calibs
+                .iter()
+                .enumerate()
+                .filter(|(_, c)| **c == Calibration::NoDigit)
+                .map(|(n, _)| (n + 1).to_string())
+                .collect();
+            if !missing.is_empty() {
+                eprintln!(
+                    "No digit in the {part} part on lines {}",
+                    missing.join(", ")
+                );
+                std::process::exit(1);
+            }
+        }
+        let calib_sum: i32 = calibs.iter().map(|c| c.value()).sum();
+        println!("The {part} sum of calibrations is {calib_sum}");
+        if summary {
+            println!("{}", Summary::new(&calibs));
+        }
+    }
+}
+
+fn language(name: &str) -> Option<&'static [&'static str; 10]> {
+    match name {
+        "en" => Some(&ENGLISH),
+        "fr" => Some(&FRENCH),
+        "de" => Some(&GERMAN),
+        _ => None,
+    }
+}
+
+// words which may be read as a digit, with their value
+#[derive(Debug, Clone)]
+struct Vocabulary(BTreeMap<String, u32>);
+
+impl Vocabulary {
+    fn digits(zero: bool) -> Self {
+        let start = if zero { 0 } else { 1 };
+        Vocabulary((start..10).map(|d| (d.to_string(), d)).collect())
+    }
+
+    fn add_words(&mut self, words: &[&str; 10], zero: bool) {
+        for (d, word) in words.iter().enumerate().skip(if zero { 0 } else { 1 }) {
+            self.0.insert(word.to_string(), d as u32);
+        }
+    }
+
+    // lines of `word digit`, blank lines and `#` comments are skipped
+    fn add_table(&mut self, table: &str) -> Result<(), String> {
+        for (n, line) in table.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (word, digit) = line
+                .split_once(char::is_whitespace)
+                .ok_or(format!("line {}: expected `word digit`", n + 1))?;
+            let digit = match digit.trim().parse() {
+                Ok(d) if d < 10 => d,
+                _ => return Err(format!("line {}: invalid digit {digit:?}", n + 1)),
+            };
+            self.0.insert(word.to_string(), digit);
+        }
+        Ok(())
+    }
+
+    // the lookahead makes every word a match, even when it overlaps the
+    // previous one, e.g. "eightwo" gives 8 and 2
+    fn regex(&self) -> String {
+        let mut words: Vec<&String> = self.0.keys().collect();
+        words.sort_by_key(|w| std::cmp::Reverse(w.len()));
+        let words: Vec<String> = words
+            .into_iter()
+            .map(|w| fancy_regex::escape(w).into())
+            .collect();
+        format!("(?=(?<digit>{}))", words.join("|"))
+    }
+}
+
+// automata of the words of a vocabulary, the first match of a line is found
+// scanning it forwards and the last one scanning it backwards, both stopping
+// at the first match
+struct Scanner {
+    forward: AhoCorasick,
+    // automaton of the reversed words, fed with the bytes of the line from the
+    // end; its first match state is the match starting last in the line
+    backward: dfa::DFA,
+    values: Vec<u32>,
+}
+
+impl Scanner {
+    fn new(vocab: &Vocabulary) -> Self {
+        let words: Vec<&String> = vocab.0.keys().collect();
+        let forward = AhoCorasick::builder()
+            .match_kind(MatchKind::LeftmostLongest)
+            .build(&words)
+            .unwrap();
+        let reversed = words.iter().map(|w| w.bytes().rev().collect::<Vec<u8>>());
+        let backward = dfa::DFA::builder()
+            .match_kind(MatchKind::Standard)
+            .build(reversed)
+            .unwrap();
+        let values = vocab.0.values().copied().collect();
+        Scanner {
+            forward,
+            backward,
+            values,
+        }
+    }
+
+    // digit of the first match and its position
+    fn first(&self, line: &str) -> Option<(u32, usize)> {
+        let m = self.forward.find(line)?;
+        Some((self.values[m.pattern()], m.start()))
+    }
+
+    // the longest of the words ending at the first match state, as the regex
+    // prefers the longest word starting at a position
+    fn last(&self, line: &str) -> Option<(u32, usize)> {
+        let dfa = &self.backward;
+        let mut sid = dfa.start_state(Anchored::No).unwrap();
+        for (i, b) in line.bytes().enumerate().rev() {
+            sid = dfa.next_state(Anchored::No, sid, b);
+            if dfa.is_match(sid) {
+                let pid = (0..dfa.match_len(sid))
+                    .map(|i| dfa.match_pattern(sid, i))
+                    .max_by_key(|&pid| dfa.pattern_len(pid))
+                    .unwrap();
+                return Some((self.values[pid], i));
+            }
+        }
+        None
+    }
+}
+
+// what a line of the calibration document gives, with the positions of the
+// matches in the line
+#[derive(Debug, PartialEq)]
+enum Calibration {
+    NoDigit,
+    // the only match is both the first and the last digit
+    SingleDigit {
+        digit: u32,
+        pos: usize,
+    },
+    Ok {
+        first: u32,
+        first_pos: usize,
+        last: u32,
+        last_pos: usize,
+    },
+}
+
+impl Calibration {
+    fn value(&self) -> i32 {
+        match *self {
+            Calibration::NoDigit => 0,
+            Calibration::SingleDigit { digit, .. } => (digit * 11) as i32,
+            Calibration::Ok { first, last, .. } => (first * 10 + last) as i32,
+        }
+    }
+}
+
+// number of lines of each kind
+#[derive(Debug, Default, PartialEq)]
+struct Summary {
+    ok: usize,
+    single_digit: usize,
+    no_digit: usize,
+}
+
+impl Summary {
+    fn new(calibs: &[Calibration]) -> Self {
+        let mut summary = Summary::default();
+        for calib in calibs {
+            match calib {
+                Calibration::NoDigit => summary.no_digit += 1,
+                Calibration::SingleDigit { .. } => summary.single_digit += 1,
+                Calibration::Ok { .. } => summary.ok += 1,
+            }
+        }
+        summary
+    }
+}
+
+impl fmt::Display for Summary {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} lines: {} ok, {} with a single digit, {} without digit",
+            self.ok + self.single_digit + self.no_digit,
+            self.ok,
+            self.single_digit,
+            self.no_digit
+        )
+    }
+}
+
+fn calibrate(scanner: &Scanner, word: &str) -> Calibration {
+    // check first match
+    let Some((first, first_pos)) = scanner.first(word) else {
+        return Calibration::NoDigit;
+    };
+
+    // check last match, which is the first one if it is the only digit
+    let (last, last_pos) = scanner.last(word).unwrap();
+    if last_pos == first_pos {
+        return Calibration::SingleDigit {
+            digit: first,
+            pos: first_pos,
+        };
+    }
+
+    Calibration::Ok {
+        first,
+        first_pos,
+        last,
+        last_pos,
+    }
+}
+
+fn compute_calibration(scanner: &Scanner, word: &str) -> i32 {
+    calibrate(scanner, word).value()
+}
+
+// the former implementation, kept as a reference for the scanner
+fn compute_calibration_regex(re: &Regex, vocab: &Vocabulary, word: &str) -> i32 {
+    let mut matches = re
+        .captures_iter(word)
+        .map(|cap| vocab.0[cap.unwrap().name("digit").unwrap().as_str()] as i32);
+
+    // check first match
+    let Some(first) = matches.next() else {
+        return 0;
+    };
 
     // check last match
-    let first_digit = calib.clone();
-    calib.push_str(match matches.last() {
-        None => &first_digit,
-        Some(cap) => match cap.unwrap().name("digit").unwrap().as_str() {
-            "1" | "one" => "1",
-            "2" | "two" => "2",
-            "3" | "three" => "3",
-            "4" | "four" => "4",
-            "5" | "five" => "5",
-            "6" | "six" => "6",
-            "7" | "seven" => "7",
-            "8" | "eight" => "8",
-            "9" | "nine" => "9",
-            _ => panic!("Unexpected last match."),
-        },
-    });
-
-    calib.parse().unwrap()
+    let last = matches.last().unwrap_or(first);
+
+    first * 10 + last
+}
+
+// lines of random letters and digits, with a word of the vocabulary now and
+// then, up to the given size in megabytes
+fn random_input(vocab: &Vocabulary, size: usize, mut seed: u64) -> String {
+    let words: Vec<&String> = vocab.0.keys().collect();
+    let mut next = || {
+        seed ^= seed << 13;
+        seed ^= seed >> 7;
+        seed ^= seed << 17;
+        seed as usize
+    };
+    let mut input = String::new();
+    while input.len() < size << 20 {
+        for _ in 0..10 + next() % 50 {
+            match next() % 20 {
+                0 => input.push_str(words[next() % words.len()]),
+                1 => input.push(char::from(b'0' + (next() % 10) as u8)),
+                _ => input.push(char::from(b'a' + (next() % 26) as u8)),
+            }
+        }
+        input.push('\n');
+    }
+    input
+}
+
+fn bench(vocab: &Vocabulary, size: usize) {
+    let input = random_input(vocab, size, 0x2023_1201);
+    let n_lines = input.lines().count();
+    println!("Calibrating {n_lines} random lines ({size} MB)");
+
+    let start = Instant::now();
+    let scanner = Scanner::new(vocab);
+    let sum: i32 = input
+        .lines()
+        .map(|w| compute_calibration(&scanner, w))
+        .sum();
+    let scanner_time = start.elapsed();
+
+    let start = Instant::now();
+    let re = Regex::new(&vocab.regex()).unwrap();
+    let regex_sum: i32 = input
+        .lines()
+        .map(|w| compute_calibration_regex(&re, vocab, w))
+        .sum();
+    let regex_time = start.elapsed();
+    assert_eq!(sum, regex_sum);
+
+    // as before, compiling the regex for every line
+    let start = Instant::now();
+    let regex_sum: i32 = input
+        .lines()
+        .map(|w| compute_calibration_regex(&Regex::new(&vocab.regex()).unwrap(), vocab, w))
+        .sum();
+    let per_line_time = start.elapsed();
+    assert_eq!(sum, regex_sum);
+
+    let throughput = |t: std::time::Duration| size as f64 / t.as_secs_f64();
+    println!(
+        "Scanner:         {scanner_time:?} ({:.1} MB/s)",
+        throughput(scanner_time)
+    );
+    println!(
+        "Regex:           {regex_time:?} ({:.1} MB/s)",
+        throughput(regex_time)
+    );
+    println!(
+        "Regex per line:  {per_line_time:?} ({:.1} MB/s)",
+        throughput(per_line_time)
+    );
 }
 
 #[cfg(test)]
@@ -60,10 +386,93 @@ mod tests {
 
     use super::*;
 
+    fn vocab(words: &[&str; 10], zero: bool) -> Vocabulary {
+        let mut vocab = Vocabulary::digits(zero);
+        vocab.add_words(words, zero);
+        vocab
+    }
+
+    // calibration with the scanner, checked against the regex
+    fn calibration(vocab: &Vocabulary, word: &str) -> i32 {
+        let c = compute_calibration(&Scanner::new(vocab), word);
+        let re = Regex::new(&vocab.regex()).unwrap();
+        assert_eq!(c, compute_calibration_regex(&re, vocab, word), "{word}");
+        c
+    }
+
     #[test]
     fn it_works() {
-        let re1 = r"(?<digit>[1-9])";
-        let c = compute_calibration(re1, "2zzdsijdsij1");
+        let c = calibration(&Vocabulary::digits(false), "2zzdsijdsij1");
         assert_eq!(c, 21);
     }
+
+    #[test]
+    fn test_languages() {
+        let english = vocab(&ENGLISH, false);
+        assert_eq!(calibration(&english, "eightwo"), 82);
+        assert_eq!(calibration(&english, "zero3zero"), 33);
+        assert_eq!(calibration(&vocab(&ENGLISH, true), "zero3zero"), 0);
+        assert_eq!(calibration(&vocab(&FRENCH, false), "xhuitroisy"), 83);
+        assert_eq!(calibration(&vocab(&GERMAN, false), "fünfzweins"), 51);
+    }
+
+    #[test]
+    fn test_table() {
+        let mut vocab = Vocabulary::digits(false);
+        vocab.add_table("# roman\ni 1\niv 4\nv 5\n\nix 9").unwrap();
+        // "iv" is preferred to "i" at the same position
+        assert_eq!(calibration(&vocab, "ivq"), 45);
+        assert_eq!(calibration(&vocab, "ixv"), 95);
+        assert!(vocab.add_table("ten 10").is_err());
+        assert!(vocab.add_table("ten").is_err());
+    }
+
+    #[test]
+    fn test_scanner() {
+        // the last match is the one starting last, not ending last
+        let mut table = Vocabulary::digits(false);
+        table.add_table("xab 1\nb 2").unwrap();
+        assert_eq!(calibration(&table, "xab"), 12);
+
+        let mut vocab = vocab(&ENGLISH, true);
+        vocab.add_words(&FRENCH, true);
+        vocab.add_words(&GERMAN, true);
+        let scanner = Scanner::new(&vocab);
+        let re = Regex::new(&vocab.regex()).unwrap();
+        for line in random_input(&vocab, 1, 42).lines().take(2000) {
+            let c = compute_calibration_regex(&re, &vocab, line);
+            assert_eq!(compute_calibration(&scanner, line), c, "{line}");
+        }
+    }
+
+    #[test]
+    fn test_calibrate() {
+        let scanner = Scanner::new(&vocab(&ENGLISH, false));
+        assert_eq!(calibrate(&scanner, "abc"), Calibration::NoDigit);
+        assert_eq!(
+            calibrate(&scanner, "xxsevenyy"),
+            Calibration::SingleDigit { digit: 7, pos: 2 }
+        );
+        assert_eq!(
+            calibrate(&scanner, "eightwo"),
+            Calibration::Ok {
+                first: 8,
+                first_pos: 0,
+                last: 2,
+                last_pos: 4
+            }
+        );
+        let calibs: Vec<_> = ["abc", "77", "a7b", "one2", "x"]
+            .iter()
+            .map(|w| calibrate(&scanner, w))
+            .collect();
+        assert_eq!(
+            Summary::new(&calibs),
+            Summary {
+                ok: 2,
+                single_digit: 1,
+                no_digit: 2
+            }
+        );
+    }
 }
@@ -1,89 +1,497 @@
 use log::debug;
 use simple_logger::SimpleLogger;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::str::FromStr;
+
+// number of cubes of each colour, such as "12 red, 13 green, 14 blue"
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct Bag(BTreeMap<String, u32>);
+
+// cubes shown in one draw, such as "3 blue, 4 red"
+type Draw = Bag;
+
+// a line "Game 11: 3 blue, 4 red; 1 red, 2 green, 6 blue"
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Game {
+    id: usize,
+    draws: Vec<Draw>,
+}
+
+// error in the line at the given number, starting from 1
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum GameError {
+    InvalidId {
+        line: usize,
+        text: String,
+    },
+    InvalidDraw {
+        line: usize,
+        text: String,
+    },
+    DuplicateId {
+        line: usize,
+        id: usize,
+        first: usize,
+    },
+}
 
 fn main() {
     SimpleLogger::new().init().unwrap();
 
     let contents = include_str!("../input");
 
-    let sum: usize = contents
-        .lines()
-        .enumerate()
-        .filter(|line| is_game_ok(line.1))
-        .map(|line| line.0 + 1)
+    // the bag is given by `--bag "12 red, 13 green, 14 blue"` or `--bag-file <path>`
+    let args: Vec<String> = std::env::args().collect();
+    let bag_str = match args.iter().position(|a| a == "--bag" || a == "--bag-file") {
+        Some(i) => {
+            let value = args.get(i + 1).unwrap_or_else(|| {
+                eprintln!("Missing value for {}", args[i]);
+                std::process::exit(1);
+            });
+            if args[i] == "--bag" {
+                value.clone()
+            } else {
+                std::fs::read_to_string(value).unwrap_or_else(|e| {
+                    eprintln!("Cannot read {value}: {e}");
+                    std::process::exit(1);
+                })
+            }
+        }
+        None => "12 red, 13 green, 14 blue".to_string(),
+    };
+    let bag: Bag = bag_str.parse().unwrap_or_else(|e| {
+        eprintln!("Invalid bag: {e}");
+        std::process::exit(1);
+    });
+    debug!("{:?}", bag);
+
+    let games = parse_games(contents).unwrap_or_else(|e| {
+        eprintln!("Invalid input: {e}");
+        std::process::exit(1);
+    });
+    debug!("{:?}", games);
+
+    match args.get(1).map(|a| a.as_str()) {
+        // `day2 feasible [--bag ...]` lists the games which are possible with the bag
+        Some("feasible") => {
+            let ids: Vec<_> = games
+                .iter()
+                .filter(|game| is_game_ok(game, &bag))
+                .map(|game| game.id.to_string())
+                .collect();
+            println!(
+                "{} games are possible with {bag}: {}",
+                ids.len(),
+                ids.join(", ")
+            );
+            return;
+        }
+        // `day2 binding [--bag ...]` shows which colours make each game impossible
+        Some("binding") => {
+            for game in games.iter() {
+                let exceeded: Vec<_> = binding_constraints(game, &bag)
+                    .iter()
+                    .map(|(colour, needed, available)| {
+                        format!("{colour} (needs {needed}, bag has {available})")
+                    })
+                    .collect();
+                if !exceeded.is_empty() {
+                    println!("Game {}: {}", game.id, exceeded.join(", "));
+                }
+            }
+            return;
+        }
+        // `day2 smallest <k>` finds a bag with as few cubes as possible making
+        // at least k games possible
+        Some("smallest") => {
+            let Some(k) = args.get(2).and_then(|k| k.parse().ok()) else {
+                eprintln!("Usage: day2 smallest <k>");
+                std::process::exit(1);
+            };
+            match smallest_bag(&games, k) {
+                Some(bag) => {
+                    let total: u32 = bag.0.values().sum();
+                    println!("The smallest bag for {k} games has {total} cubes: {bag}");
+                }
+                None => println!("There are fewer than {k} games"),
+            }
+            return;
+        }
+        _ => (),
+    }
+
+    let sum: usize = games
+        .iter()
+        .filter(|game| is_game_ok(game, &bag))
+        .map(|game| game.id)
         .sum();
 
-    let power_sum: i32 = contents.lines().map(power).sum();
+    let colours = input_colours(&games);
+    let power_sum: u64 = games
+        .iter()
+        .map(|game| power(&game.min_bag(), &colours))
+        .sum();
 
-    println!("The sum of lines of possible games is {sum}");
+    println!("The sum of ids of possible games is {sum}");
     println!("The sum of powers is {power_sum}");
 }
 
-fn is_game_ok(game: &str) -> bool {
-    debug!("=== FUNCTION is_game_ok ===");
-    debug!("{game}");
-    let mut game_ok = true;
-    let i = game.find(':').unwrap();
-    for draw in game[i + 2..].split("; ") {
-        debug!("{draw}");
-        for balls in draw.split(", ") {
-            debug!("{balls}");
+impl Bag {
+    fn get(&self, colour: &str) -> u32 {
+        self.0.get(colour).copied().unwrap_or(0)
+    }
+
+    // whether this bag has at least as many cubes of each colour as `other`
+    fn contains(&self, other: &Bag) -> bool {
+        other.0.iter().all(|(colour, n)| self.get(colour) >= *n)
+    }
+}
+
+impl fmt::Display for Bag {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let cubes: Vec<_> = self.0.iter().map(|(c, n)| format!("{n} {c}")).collect();
+        write!(f, "{}", cubes.join(", "))
+    }
+}
+
+impl FromStr for Bag {
+    type Err = String;
+
+    // colours are separated by commas or new lines
+    fn from_str(s: &str) -> Result<Bag, String> {
+        let mut bag = Bag::default();
+        for balls in s
+            .split([',', '\n'])
+            .map(|b| b.trim())
+            .filter(|b| !b.is_empty())
+        {
             let mut it = balls.split_ascii_whitespace();
-            let n: i32 = it.next().unwrap().parse().unwrap();
-            let color = it.next().unwrap();
-            match color {
-                "red" => {
-                    if n > 12 {
-                        game_ok = false;
-                        break;
-                    }
-                }
-                "green" => {
-                    if n > 13 {
-                        game_ok = false;
-                        break;
-                    }
-                }
-                "blue" => {
-                    if n > 14 {
-                        game_ok = false;
-                        break;
-                    }
-                }
-                _ => panic!("Unexpected color"),
+            let (Some(n), Some(colour), None) = (it.next(), it.next(), it.next()) else {
+                return Err(format!("expected \"<number> <colour>\", got \"{balls}\""));
+            };
+            let n: u32 = n
+                .parse()
+                .map_err(|_| format!("invalid number in \"{balls}\""))?;
+            // a colour given twice is ambiguous, its counts could add up or not
+            if bag.0.insert(colour.to_string(), n).is_some() {
+                return Err(format!("colour {colour} given twice"));
             }
         }
+        Ok(bag)
     }
-    game_ok
 }
 
-fn power(game: &str) -> i32 {
-    let mut min_balls = [0, 0, 0];
-    let i = game.find(':').unwrap();
-    for draw in game[i + 2..].split("; ") {
-        for balls in draw.split(", ") {
-            let mut it = balls.split_ascii_whitespace();
-            let n: i32 = it.next().unwrap().parse().unwrap();
-            let color = it.next().unwrap();
-            match color {
-                "red" => {
-                    if n > min_balls[0] {
-                        min_balls[0] = n;
-                    }
-                }
-                "green" => {
-                    if n > min_balls[1] {
-                        min_balls[1] = n;
-                    }
+fn parse_game(n: usize, line: &str) -> Result<Game, GameError> {
+    let (header, draws) = line.split_once(": ").ok_or(GameError::InvalidId {
+        line: n,
+        text: line.to_string(),
+    })?;
+    let id = header
+        .strip_prefix("Game ")
+        .and_then(|id| id.parse().ok())
+        .ok_or(GameError::InvalidId {
+            line: n,
+            text: header.to_string(),
+        })?;
+    let draws = draws
+        .split("; ")
+        .map(|draw| {
+            draw.parse::<Draw>()
+                .map_err(|e| GameError::InvalidDraw { line: n, text: e })
+        })
+        .collect::<Result<_, _>>()?;
+    Ok(Game { id, draws })
+}
+
+// parses one game per line, game ids being unique
+fn parse_games(contents: &str) -> Result<Vec<Game>, GameError> {
+    let mut first_line: HashMap<usize, usize> = HashMap::new();
+    let mut games = Vec::new();
+    for (i, line) in contents.lines().enumerate() {
+        let game = parse_game(i + 1, line)?;
+        if let Some(&first) = first_line.get(&game.id) {
+            return Err(GameError::DuplicateId {
+                line: i + 1,
+                id: game.id,
+                first,
+            });
+        }
+        first_line.insert(game.id, i + 1);
+        games.push(game);
+    }
+    Ok(games)
+}
+
+impl fmt::Display for GameError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GameError::InvalidId { line, text } => {
+                write!(f, "line {line}: expected \"Game <id>\", got \"{text}\"")
+            }
+            GameError::InvalidDraw { line, text } => write!(f, "line {line}: {text}"),
+            GameError::DuplicateId { line, id, first } => {
+                write!(f, "line {line}: game {id} already appears at line {first}")
+            }
+        }
+    }
+}
+
+impl Game {
+    // smallest bag with which the game is possible
+    fn min_bag(&self) -> Bag {
+        let mut bag = Bag::default();
+        for draw in self.draws.iter() {
+            for (colour, n) in draw.0.iter() {
+                let min = bag.0.entry(colour.clone()).or_insert(0);
+                if n > min {
+                    *min = *n;
                 }
-                "blue" => {
-                    if n > min_balls[2] {
-                        min_balls[2] = n;
+            }
+        }
+        bag
+    }
+}
+
+fn is_game_ok(game: &Game, bag: &Bag) -> bool {
+    debug!("=== FUNCTION is_game_ok ===");
+    debug!("{:?}", game);
+    bag.contains(&game.min_bag())
+}
+
+// all colours appearing in the games, sorted
+fn input_colours(games: &[Game]) -> Vec<String> {
+    let mut colours: Vec<String> = games
+        .iter()
+        .flat_map(|game| game.min_bag().0.into_keys())
+        .collect();
+    colours.sort();
+    colours.dedup();
+    colours
+}
+
+// colours of which the game needs more cubes than the bag has, with the number
+// of cubes needed and available
+fn binding_constraints(game: &Game, bag: &Bag) -> Vec<(String, u32, u32)> {
+    game.min_bag()
+        .0
+        .into_iter()
+        .filter(|(colour, n)| *n > bag.get(colour))
+        .map(|(colour, n)| {
+            let available = bag.get(&colour);
+            (colour, n, available)
+        })
+        .collect()
+}
+
+// bag with the fewest cubes in total with which at least k games are possible;
+// the number of cubes of each colour is that needed by one of the games, and
+// for the last colour it is the k-th smallest need of the games still possible
+fn smallest_bag(games: &[Game], k: usize) -> Option<Bag> {
+    if k > games.len() {
+        return None;
+    }
+    if k == 0 {
+        return Some(Bag::default());
+    }
+    let min_bags: Vec<Bag> = games.iter().map(|g| g.min_bag()).collect();
+    let colours = input_colours(games);
+    let mut best: Option<(u32, Bag)> = None;
+    search_bag(&min_bags, &colours, k, Bag::default(), 0, &mut best);
+    best.map(|b| b.1)
+}
+
+fn search_bag(
+    min_bags: &[Bag],
+    colours: &[String],
+    k: usize,
+    bag: Bag,
+    total: u32,
+    best: &mut Option<(u32, Bag)>,
+) {
+    if min_bags.len() < k || best.as_ref().is_some_and(|b| b.0 <= total) {
+        return;
+    }
+    let Some((colour, rest)) = colours.split_first() else {
+        *best = Some((total, bag));
+        return;
+    };
+    let mut needs: Vec<u32> = min_bags.iter().map(|b| b.get(colour)).collect();
+    needs.sort();
+    if rest.is_empty() {
+        let n = needs[k - 1];
+        let mut bag = bag;
+        bag.0.insert(colour.clone(), n);
+        search_bag(min_bags, rest, k, bag, total + n, best);
+        return;
+    }
+    needs.dedup();
+    for n in needs {
+        let fitting: Vec<Bag> = min_bags
+            .iter()
+            .filter(|b| b.get(colour) <= n)
+            .cloned()
+            .collect();
+        let mut bag = bag.clone();
+        bag.0.insert(colour.clone(), n);
+        search_bag(&fitting, rest, k, bag, total + n, best);
+    }
+}
+
+// product of the number of cubes of each colour, a colour missing from the bag
+// counting as 0
+fn power(bag: &Bag, colours: &[String]) -> u64 {
+    colours.iter().map(|c| bag.get(c) as u64).product()
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    const EXAMPLE: &str = "\
+Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green
+Game 2: 1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue
+Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red
+Game 4: 1 green, 3 red, 6 blue; 3 green, 6 red; 3 green, 15 blue, 14 red
+Game 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green";
+
+    #[test]
+    fn test_parse_bag() {
+        let bag: Bag = "12 red, 13 green\n14 blue".parse().unwrap();
+        assert_eq!(bag.get("green"), 13);
+        assert_eq!(bag.get("blue"), 14);
+        assert_eq!(bag.get("yellow"), 0);
+        assert!("12 red, green".parse::<Bag>().is_err());
+        assert!("x red".parse::<Bag>().is_err());
+        assert!("4 blue, 3 blue".parse::<Bag>().is_err());
+        assert!(parse_game(1, "Game 1: 4 blue, 3 blue; 2 red").is_err());
+    }
+
+    fn games(contents: &str) -> Vec<Game> {
+        parse_games(contents).unwrap()
+    }
+
+    #[test]
+    fn test_parse_game() {
+        let game = parse_game(1, "Game 11: 3 blue, 4 red; 2 green").unwrap();
+        assert_eq!(game.id, 11);
+        assert_eq!(game.draws.len(), 2);
+        assert_eq!(game.draws[0].get("red"), 4);
+        assert_eq!(game.draws[1].get("green"), 2);
+        assert_eq!(
+            parse_game(3, "Gaem 1: 3 blue"),
+            Err(GameError::InvalidId {
+                line: 3,
+                text: "Gaem 1".to_string()
+            })
+        );
+        assert!(matches!(
+            parse_game(3, "Game 1: 3 blue; red"),
+            Err(GameError::InvalidDraw { line: 3, .. })
+        ));
+        assert!(matches!(
+            parse_game(3, "Game 1 3 blue"),
+            Err(GameError::InvalidId { line: 3, .. })
+        ));
+    }
+
+    #[test]
+    fn test_example() {
+        let games = games(EXAMPLE);
+        let bag: Bag = "12 red, 13 green, 14 blue".parse().unwrap();
+        let ok: Vec<_> = games.iter().map(|g| is_game_ok(g, &bag)).collect();
+        assert_eq!(ok, [true, true, false, false, true]);
+        let colours = input_colours(&games);
+        let powers: Vec<_> = games
+            .iter()
+            .map(|g| power(&g.min_bag(), &colours))
+            .collect();
+        assert_eq!(powers, [48, 12, 1560, 630, 36]);
+    }
+
+    #[test]
+    fn test_game_ids() {
+        let games = games("Game 7: 1 red\nGame 3: 20 red\nGame 12: 2 blue");
+        let bag: Bag = "12 red, 13 green, 14 blue".parse().unwrap();
+        let sum: usize = games
+            .iter()
+            .filter(|g| is_game_ok(g, &bag))
+            .map(|g| g.id)
+            .sum();
+        assert_eq!(sum, 19);
+        assert_eq!(
+            parse_games("Game 1: 1 red\nGame 2: 1 red\nGame 1: 2 red"),
+            Err(GameError::DuplicateId {
+                line: 3,
+                id: 1,
+                first: 1
+            })
+        );
+    }
+
+    #[test]
+    fn test_other_colours() {
+        let games = games("Game 1: 2 red, 1 yellow; 3 yellow\nGame 2: 2 red, 5 purple");
+        let bag: Bag = "4 red, 3 yellow".parse().unwrap();
+        let ok: Vec<_> = games.iter().map(|g| is_game_ok(g, &bag)).collect();
+        assert_eq!(ok, [true, false]);
+        let colours = input_colours(&games);
+        assert_eq!(colours, ["purple", "red", "yellow"]);
+        // game 1 has no purple cube
+        assert_eq!(power(&games[0].min_bag(), &colours), 0);
+        assert_eq!(power(&games[0].min_bag(), &colours[1..]), 6);
+    }
+
+    #[test]
+    fn test_binding_constraints() {
+        let games = games(EXAMPLE);
+        let bag: Bag = "12 red, 13 green, 14 blue".parse().unwrap();
+        assert_eq!(binding_constraints(&games[0], &bag), []);
+        assert_eq!(
+            binding_constraints(&games[2], &bag),
+            [("red".to_string(), 20, 12)]
+        );
+        assert_eq!(
+            binding_constraints(&games[3], &bag),
+            [("blue".to_string(), 15, 14), ("red".to_string(), 14, 12)]
+        );
+    }
+
+    // smallest total of a bag making at least k games possible, trying all bags
+    fn brute_force_smallest_total(games: &[Game], k: usize) -> u32 {
+        let mut best = u32::MAX;
+        for red in 0..=20 {
+            for green in 0..=13 {
+                for blue in 0..=15 {
+                    let bag = Bag(BTreeMap::from([
+                        ("red".to_string(), red),
+                        ("green".to_string(), green),
+                        ("blue".to_string(), blue),
+                    ]));
+                    if games.iter().filter(|g| is_game_ok(g, &bag)).count() >= k {
+                        best = best.min(red + green + blue);
                     }
                 }
-                _ => panic!("Unexpected color"),
             }
         }
+        best
+    }
+
+    #[test]
+    fn test_smallest_bag() {
+        let games = games(EXAMPLE);
+        for k in 0..=5 {
+            let bag = smallest_bag(&games, k).unwrap();
+            assert!(games.iter().filter(|g| is_game_ok(g, &bag)).count() >= k);
+            let total: u32 = bag.0.values().sum();
+            assert_eq!(total, brute_force_smallest_total(&games, k), "k = {k}");
+        }
+        // games 2 and 5 are the cheapest pair
+        assert_eq!(
+            smallest_bag(&games, 2).unwrap(),
+            "6 red, 3 green, 4 blue".parse().unwrap()
+        );
+        assert_eq!(smallest_bag(&games, 6), None);
     }
-    min_balls[0] * min_balls[1] * min_balls[2]
 }
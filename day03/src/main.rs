@@ -1,166 +1,524 @@
-use regex::Regex;
+use std::collections::HashMap;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader};
+use std::ops::RangeInclusive;
+use std::str::FromStr;
 
 fn main() {
     let contents = include_str!("../input");
 
-    let n_lines = contents.lines().count();
-    let n_rows = contents.lines().next().unwrap().len();
+    let schematic = Schematic::new(contents);
 
-    let mut is_special_char = vec![vec![false; n_rows]; n_lines];
+    let args: Vec<String> = std::env::args().collect();
 
-    for line in contents.lines().enumerate() {
-        for c in line.1.chars().enumerate() {
-            if !c.1.is_ascii_digit() && c.1 != '.' {
-                is_special_char[line.0][c.0] = true;
+    // the gears are given by `--gear <symbols>:<count>:<reduction>`, e.g.
+    // `--gear "*#:2+:sum"`, the default being `*:2:product`
+    let rule: GearRule = match args.iter().position(|a| a == "--gear") {
+        Some(i) => {
+            let value = args.get(i + 1).unwrap_or_else(|| {
+                eprintln!("Missing value for --gear");
+                std::process::exit(1);
+            });
+            value.parse().unwrap_or_else(|e| {
+                eprintln!("Invalid gear rule {value}: {e}");
+                std::process::exit(1);
+            })
+        }
+        None => GearRule::default(),
+    };
+
+    // `day3 shared` lists the numbers adjacent to two symbols or more
+    if args.get(1).map(|a| a.as_str()) == Some("shared") {
+        for (n, number) in schematic.numbers.iter().enumerate() {
+            let symbols: Vec<String> = schematic
+                .symbols_of(n)
+                .map(|s| format!("{} at {}:{}", s.c, s.row + 1, s.col + 1))
+                .collect();
+            if symbols.len() >= 2 {
+                println!(
+                    "{} at {}:{} is adjacent to {}",
+                    number.value,
+                    number.row + 1,
+                    number.start + 1,
+                    symbols.join(", ")
+                );
             }
         }
+        return;
     }
 
-    let re = Regex::new(r"[0-9]+").unwrap();
-    let mut sum: usize = 0;
-    for line in contents.lines().enumerate() {
-        for cap in re.captures_iter(line.1) {
-            let m = cap.get(0).unwrap();
-            let i = m.start();
-            let j = m.end();
-            let num = m.as_str();
-            if is_part_number(line.0, i, j, &is_special_char, n_lines, n_rows) {
-                sum += num.parse::<usize>().unwrap();
+    // `day3 stream [path]` processes a schematic from a file or the standard
+    // input row by row, printing the part numbers and gears as they are found
+    if args.get(1).map(|a| a.as_str()) == Some("stream") {
+        let (mut sum, mut gear_sum) = (0, 0);
+        let emit = |event| match event {
+            Event::PartNumber(n) => {
+                println!("Part number {} at {}:{}", n.value, n.row + 1, n.start + 1);
+                sum += n.value;
+            }
+            Event::Gear(s, ratio) => {
+                println!(
+                    "Gear {} at {}:{} with ratio {ratio}",
+                    s.c,
+                    s.row + 1,
+                    s.col + 1
+                );
+                gear_sum += ratio;
             }
+        };
+        let result = match args.get(2).filter(|a| !a.starts_with("--")) {
+            Some(path) => File::open(path).and_then(|f| stream(BufReader::new(f), &rule, emit)),
+            None => stream(io::stdin().lock(), &rule, emit),
+        };
+        if let Err(e) = result {
+            eprintln!("Cannot read the schematic: {e}");
+            std::process::exit(1);
         }
+        println!("The sum of part numbers is {sum}");
+        println!("The sum of gear numbers is {gear_sum}");
+        return;
     }
 
+    let sum: usize = schematic.part_numbers().map(|n| n.value).sum();
+
     println!("The sum of part numbers is {sum}");
 
-    let mut gear_tab = vec![vec![(false, 0, 1); n_rows]; n_lines];
+    let gear_sum: usize = schematic.gear_ratios(&rule).map(|g| g.1).sum();
+
+    println!("The sum of gear numbers is {gear_sum}");
+}
+
+/// A number of the schematic, on a row from the column `start` to `end` excluded.
+#[derive(Debug, Clone, PartialEq)]
+struct Number {
+    value: usize,
+    row: usize,
+    start: usize,
+    end: usize,
+}
+
+/// A char of the schematic which is neither a digit nor a `.`.
+#[derive(Debug, Clone, PartialEq)]
+struct Symbol {
+    c: char,
+    row: usize,
+    col: usize,
+}
 
-    for line in contents.lines().enumerate() {
-        for c in line.1.chars().enumerate() {
-            if c.1 == '*' {
-                gear_tab[line.0][c.0].0 = true;
+/// The numbers and symbols of a row, sorted by column.
+#[derive(Debug, Default)]
+struct Row {
+    numbers: Vec<Number>,
+    symbols: Vec<Symbol>,
+}
+
+impl Row {
+    fn parse(row: usize, line: &str) -> Self {
+        let mut numbers = Vec::new();
+        let mut symbols = Vec::new();
+        let mut number: Option<Number> = None;
+        for (col, c) in line.chars().enumerate() {
+            match c.to_digit(10) {
+                Some(d) => {
+                    let n = number.get_or_insert(Number {
+                        value: 0,
+                        row,
+                        start: col,
+                        end: col,
+                    });
+                    n.value = n.value * 10 + d as usize;
+                    n.end = col + 1;
+                }
+                None => {
+                    numbers.extend(number.take());
+                    if c != '.' {
+                        symbols.push(Symbol { c, row, col });
+                    }
+                }
             }
         }
+        numbers.extend(number);
+        Row { numbers, symbols }
     }
 
-    for line in contents.lines().enumerate() {
-        for cap in re.captures_iter(line.1) {
-            let m = cap.get(0).unwrap();
-            let i = m.start();
-            let j = m.end();
-            let num = m.as_str().parse().unwrap();
-            update_gear_tab(line.0, i, j, num, &mut gear_tab, n_lines, n_rows)
-        }
+    /// Symbols with a column in the range.
+    fn symbols_in(&self, cols: RangeInclusive<usize>) -> &[Symbol] {
+        let from = self.symbols.partition_point(|s| s.col < *cols.start());
+        let to = self.symbols.partition_point(|s| s.col <= *cols.end());
+        &self.symbols[from..to]
     }
 
-    let gear_sum: usize = gear_tab
-        .iter()
-        .flat_map(|l| l.iter())
-        .filter(|cell| cell.1 == 2)
-        .map(|cell| cell.2)
-        .sum();
+    /// Numbers next to the column, including diagonally.
+    fn numbers_around(&self, col: usize) -> impl Iterator<Item = &Number> {
+        // numbers do not overlap, so they are sorted by end as well
+        let from = self.numbers.partition_point(|n| n.end < col);
+        self.numbers[from..]
+            .iter()
+            .take_while(move |n| n.start <= col + 1)
+    }
+}
 
-    println!("The sum of gear numbers is {gear_sum}");
+/// What the streaming processor finds in a row.
+#[derive(Debug, PartialEq)]
+enum Event {
+    PartNumber(Number),
+    Gear(Symbol, usize),
 }
 
-/// Takes the position of a number specified by the line index, the row start index, and the row end index
-/// and a tab of booleans of size n_lines times n_rows indicating where special chars are locates
-/// and returns true if the number is adjacent to a special char.
-fn is_part_number(
-    l: usize,
-    i: usize,
-    j: usize,
-    tab: &[Vec<bool>],
-    n_lines: usize,
-    n_rows: usize,
-) -> bool {
-    // check previous line
-    if l > 0 {
-        if i > 0 && tab[l - 1][i - 1] {
-            return true;
+/// Processes a schematic row by row, keeping only the previous, current and
+/// next rows, and emits the part numbers and gears of a row once the next one
+/// is read.
+fn stream<R: BufRead>(reader: R, rule: &GearRule, mut emit: impl FnMut(Event)) -> io::Result<()> {
+    let mut rows = reader
+        .lines()
+        .enumerate()
+        .map(|(r, line)| line.map(|line| Row::parse(r, &line)));
+    let mut prev = Row::default();
+    let Some(mut cur) = rows.next().transpose()? else {
+        return Ok(());
+    };
+    let last = Row::default();
+    loop {
+        let next = rows.next().transpose()?;
+        let window = [&prev, &cur, next.as_ref().unwrap_or(&last)];
+
+        for number in cur.numbers.iter() {
+            let cols = number.start.saturating_sub(1)..=number.end;
+            if window
+                .iter()
+                .any(|row| !row.symbols_in(cols.clone()).is_empty())
+            {
+                emit(Event::PartNumber(number.clone()));
+            }
         }
-        for k in i..j {
-            if tab[l - 1][k] {
-                return true;
+        for symbol in cur.symbols.iter().filter(|s| rule.symbols.contains(&s.c)) {
+            let numbers: Vec<&Number> = window
+                .iter()
+                .flat_map(|row| row.numbers_around(symbol.col))
+                .collect();
+            if rule.count.matches(numbers.len()) {
+                let ratio = rule.reduction.reduce(numbers.iter().map(|n| n.value));
+                emit(Event::Gear(symbol.clone(), ratio));
             }
         }
-        if j < n_rows && tab[l - 1][j] {
-            return true;
+
+        match next {
+            Some(next) => prev = std::mem::replace(&mut cur, next),
+            None => return Ok(()),
         }
     }
+}
+
+/// Number of numbers a gear must be adjacent to.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Count {
+    Exactly(usize),
+    AtLeast(usize),
+}
+
+/// How the numbers adjacent to a gear are combined into its ratio.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Reduction {
+    Product,
+    Sum,
+    Max,
+}
+
+/// What makes a symbol a gear, and how its ratio is computed.
+#[derive(Debug, Clone, PartialEq)]
+struct GearRule {
+    symbols: Vec<char>,
+    count: Count,
+    reduction: Reduction,
+}
 
-    // check current line
-    if i > 0 && tab[l][i - 1] {
-        return true;
+impl Count {
+    fn matches(&self, n: usize) -> bool {
+        match *self {
+            Count::Exactly(k) => n == k,
+            Count::AtLeast(k) => n >= k,
+        }
     }
-    if j < n_rows && tab[l][j] {
-        return true;
+}
+
+impl Reduction {
+    fn reduce(&self, values: impl Iterator<Item = usize>) -> usize {
+        match self {
+            Reduction::Product => values.product(),
+            Reduction::Sum => values.sum(),
+            Reduction::Max => values.max().unwrap_or(0),
+        }
     }
+}
 
-    // check following line
-    if l < n_lines - 1 {
-        if i > 0 && tab[l + 1][i - 1] {
-            return true;
+impl Default for GearRule {
+    // gears of the puzzle, `*` next to exactly two numbers
+    fn default() -> Self {
+        GearRule {
+            symbols: vec!['*'],
+            count: Count::Exactly(2),
+            reduction: Reduction::Product,
         }
-        for k in i..j {
-            if tab[l + 1][k] {
-                return true;
-            }
+    }
+}
+
+impl FromStr for Count {
+    type Err = String;
+
+    // `2` for exactly two numbers, `2+` for at least two
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (n, at_least) = match s.strip_suffix('+') {
+            Some(n) => (n, true),
+            None => (s, false),
+        };
+        let n = n.parse().map_err(|_| format!("invalid count {s:?}"))?;
+        Ok(if at_least {
+            Count::AtLeast(n)
+        } else {
+            Count::Exactly(n)
+        })
+    }
+}
+
+impl FromStr for Reduction {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "product" => Ok(Reduction::Product),
+            "sum" => Ok(Reduction::Sum),
+            "max" => Ok(Reduction::Max),
+            _ => Err(format!(
+                "invalid reduction {s:?}, expected product, sum or max"
+            )),
         }
-        if j < n_rows && tab[l + 1][j] {
-            return true;
+    }
+}
+
+impl FromStr for GearRule {
+    type Err = String;
+
+    // `<symbols>:<count>:<reduction>`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut fields = s.split(':');
+        let (Some(symbols), Some(count), Some(reduction), None) =
+            (fields.next(), fields.next(), fields.next(), fields.next())
+        else {
+            return Err("expected <symbols>:<count>:<reduction>".to_string());
+        };
+        if symbols.is_empty() {
+            return Err("no gear symbol".to_string());
         }
+        Ok(GearRule {
+            symbols: symbols.chars().collect(),
+            count: count.parse()?,
+            reduction: reduction.parse()?,
+        })
     }
+}
+
+impl fmt::Display for GearRule {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let symbols: String = self.symbols.iter().collect();
+        let count = match self.count {
+            Count::Exactly(n) => n.to_string(),
+            Count::AtLeast(n) => format!("{n}+"),
+        };
+        let reduction = format!("{:?}", self.reduction).to_lowercase();
+        write!(f, "{symbols}:{count}:{reduction}")
+    }
+}
 
-    false
+/// The numbers and symbols of a schematic, and the bipartite graph of which
+/// numbers are adjacent to which symbols, including diagonally.
+#[derive(Debug)]
+struct Schematic {
+    numbers: Vec<Number>,
+    symbols: Vec<Symbol>,
+    // indices of the symbols adjacent to each number, and conversely
+    number_edges: Vec<Vec<usize>>,
+    symbol_edges: Vec<Vec<usize>>,
 }
 
-fn update_gear_tab(
-    l: usize,
-    i: usize,
-    j: usize,
-    num: usize,
-    tab: &mut [Vec<(bool, usize, usize)>],
-    n_lines: usize,
-    n_rows: usize,
-) {
-    // update previous line
-    if l > 0 {
-        if i > 0 {
-            update_cell(&mut tab[l - 1][i - 1], num);
+impl Schematic {
+    fn new(contents: &str) -> Self {
+        let mut numbers = Vec::new();
+        let mut symbols = Vec::new();
+        for (row, line) in contents.lines().enumerate() {
+            let row = Row::parse(row, line);
+            numbers.extend(row.numbers);
+            symbols.extend(row.symbols);
         }
-        for k in i..j {
-            update_cell(&mut tab[l - 1][k], num);
+
+        let positions: HashMap<(usize, usize), usize> = symbols
+            .iter()
+            .enumerate()
+            .map(|(s, symbol)| ((symbol.row, symbol.col), s))
+            .collect();
+        let mut number_edges = vec![Vec::new(); numbers.len()];
+        let mut symbol_edges = vec![Vec::new(); symbols.len()];
+        for (n, number) in numbers.iter().enumerate() {
+            for row in number.row.saturating_sub(1)..=number.row + 1 {
+                for col in number.start.saturating_sub(1)..=number.end {
+                    if let Some(&s) = positions.get(&(row, col)) {
+                        number_edges[n].push(s);
+                        symbol_edges[s].push(n);
+                    }
+                }
+            }
         }
-        if j < n_rows {
-            update_cell(&mut tab[l - 1][j], num);
+
+        Schematic {
+            numbers,
+            symbols,
+            number_edges,
+            symbol_edges,
         }
     }
 
-    // update current line
-    if i > 0 {
-        update_cell(&mut tab[l][i - 1], num);
+    /// Symbols adjacent to the number of the given index.
+    fn symbols_of(&self, n: usize) -> impl Iterator<Item = &Symbol> {
+        self.number_edges[n].iter().map(|&s| &self.symbols[s])
     }
-    if j < n_rows {
-        update_cell(&mut tab[l][j], num);
+
+    /// Numbers adjacent to the symbol of the given index.
+    fn numbers_of(&self, s: usize) -> impl Iterator<Item = &Number> {
+        self.symbol_edges[s].iter().map(|&n| &self.numbers[n])
     }
 
-    // update following line
-    if l < n_lines - 1 {
-        if i > 0 {
-            update_cell(&mut tab[l + 1][i - 1], num);
-        }
-        for k in i..j {
-            update_cell(&mut tab[l + 1][k], num);
-        }
-        if j < n_rows {
-            update_cell(&mut tab[l + 1][j], num);
-        }
+    /// Symbols which are gears according to the rule, with their ratio.
+    fn gear_ratios<'a>(&'a self, rule: &'a GearRule) -> impl Iterator<Item = (&'a Symbol, usize)> {
+        self.symbols
+            .iter()
+            .enumerate()
+            .filter(|(s, symbol)| {
+                rule.symbols.contains(&symbol.c) && rule.count.matches(self.symbol_edges[*s].len())
+            })
+            .map(|(s, symbol)| {
+                let values = self.numbers_of(s).map(|n| n.value);
+                (symbol, rule.reduction.reduce(values))
+            })
+    }
+
+    /// Numbers adjacent to at least one symbol.
+    fn part_numbers(&self) -> impl Iterator<Item = &Number> {
+        self.numbers
+            .iter()
+            .zip(&self.number_edges)
+            .filter(|(_, edges)| !edges.is_empty())
+            .map(|(number, _)| number)
     }
 }
 
-fn update_cell(cell: &mut (bool, usize, usize), num: usize) {
-    if cell.0 {
-        cell.1 += 1;
-        cell.2 *= num;
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    const EXAMPLE: &str = "467..114..
+...*......
+..35..633.
+......#...
+617*......
+.....+.58.
+..592.....
+......755.
+...$.*....
+.664.598..";
+
+    #[test]
+    fn test_schematic() {
+        let schematic = Schematic::new(EXAMPLE);
+        assert_eq!(schematic.numbers.len(), 10);
+        assert_eq!(schematic.symbols.len(), 6);
+        assert_eq!(
+            schematic.numbers[1],
+            Number {
+                value: 114,
+                row: 0,
+                start: 5,
+                end: 8
+            }
+        );
+        let sum: usize = schematic.part_numbers().map(|n| n.value).sum();
+        assert_eq!(sum, 4361);
+
+        // 467 and 35 share the first gear
+        let gear: Vec<usize> = schematic.numbers_of(0).map(|n| n.value).collect();
+        assert_eq!(gear, [467, 35]);
+
+        // a number between two symbols, on a ragged row
+        let schematic = Schematic::new("..\n#12*\n");
+        let symbols: Vec<char> = schematic.symbols_of(0).map(|s| s.c).collect();
+        assert_eq!(symbols, ['#', '*']);
+    }
+
+    #[test]
+    fn test_gear_rules() {
+        let schematic = Schematic::new(EXAMPLE);
+        let gear_sum = |rule: &str| -> usize {
+            let rule: GearRule = rule.parse().unwrap();
+            schematic.gear_ratios(&rule).map(|g| g.1).sum()
+        };
+        assert_eq!(gear_sum("*:2:product"), 467835);
+        assert_eq!(gear_sum("*:1+:sum"), 467 + 35 + 617 + 755 + 598);
+        assert_eq!(gear_sum("*#+:1:max"), 617 + 633 + 592);
+
+        let rule: GearRule = "*#:2+:sum".parse().unwrap();
+        assert_eq!(rule.to_string(), "*#:2+:sum");
+        assert_eq!(GearRule::default().to_string(), "*:2:product");
+        assert!("*:2".parse::<GearRule>().is_err());
+        assert!(":2:sum".parse::<GearRule>().is_err());
+        assert!("*:two:sum".parse::<GearRule>().is_err());
+        assert!("*:2:mean".parse::<GearRule>().is_err());
+    }
+
+    #[test]
+    fn test_stream() {
+        let mut events = Vec::new();
+        stream(EXAMPLE.as_bytes(), &GearRule::default(), |e| events.push(e)).unwrap();
+        let schematic = Schematic::new(EXAMPLE);
+        let parts: Vec<Event> = schematic
+            .part_numbers()
+            .map(|n| Event::PartNumber(n.clone()))
+            .collect();
+        let (streamed_parts, gears): (Vec<Event>, Vec<Event>) = events
+            .into_iter()
+            .partition(|e| matches!(e, Event::PartNumber(_)));
+        assert_eq!(streamed_parts, parts);
+        let ratios: Vec<usize> = gears
+            .iter()
+            .map(|e| match e {
+                Event::Gear(_, ratio) => *ratio,
+                _ => unreachable!(),
+            })
+            .collect();
+        assert_eq!(ratios, [16345, 451490]);
+
+        // ragged rows, with symbols beyond the end of the neighbouring rows
+        let ragged = "1\n..*....\n3.........7*\n......#\n";
+        let rule: GearRule = "*#:1+:sum".parse().unwrap();
+        let mut events = Vec::new();
+        stream(ragged.as_bytes(), &rule, |e| events.push(e)).unwrap();
+        let schematic = Schematic::new(ragged);
+        let expected: Vec<Event> = schematic
+            .part_numbers()
+            .map(|n| Event::PartNumber(n.clone()))
+            .collect();
+        let streamed: Vec<&Event> = events
+            .iter()
+            .filter(|e| matches!(e, Event::PartNumber(_)))
+            .collect();
+        assert_eq!(streamed, expected.iter().collect::<Vec<_>>());
+        let gear_sum: usize = schematic.gear_ratios(&rule).map(|g| g.1).sum();
+        let streamed_sum: usize = events
+            .iter()
+            .map(|e| match e {
+                Event::Gear(_, ratio) => *ratio,
+                _ => 0,
+            })
+            .sum();
+        assert_eq!(streamed_sum, gear_sum);
     }
 }
@@ -1,89 +1,356 @@
 use log::debug;
 use simple_logger::SimpleLogger;
+use std::fmt;
 
 type Maps = Vec<Vec<Vec<usize>>>;
 
+// half-open interval start..end
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+struct Interval {
+    start: usize,
+    end: usize,
+}
+
+// a range "dest src len" of an almanac map
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+struct Segment {
+    dest: usize,
+    src: usize,
+    len: usize,
+}
+
+// an almanac map as segments sorted by source, numbers outside of all
+// segments being mapped to themselves
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct RangeMap {
+    segments: Vec<Segment>,
+}
+
+// an "X-to-Y map:" section of the almanac
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct CategoryMap {
+    from: String,
+    to: String,
+    ranges: Vec<Vec<usize>>,
+    // line number of each range
+    lines: Vec<usize>,
+}
+
+// seeds and maps between categories; each category is the source of at most one map
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Almanac {
+    seeds: Vec<usize>,
+    maps: Vec<CategoryMap>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum AlmanacError {
+    MissingSeeds,
+    InvalidHeader { line: usize, text: String },
+    InvalidRange { line: usize, text: String },
+    DuplicateMap { line: usize, from: String },
+    UnknownCategory(String),
+    NoPath { from: String, to: String },
+    Cycle(Vec<String>),
+}
+
+// problem found in a range of an "X-to-Y map:" section
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct RangeIssue {
+    map: String,
+    line: usize,
+    kind: RangeIssueKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum RangeIssueKind {
+    ZeroLength,
+    // src + len or dest + len does not fit in a usize
+    Overflow,
+    // source range overlapping the one at the given line
+    Overlap(usize),
+}
+
 fn main() {
     SimpleLogger::new().init().unwrap();
 
     let contents = include_str!("../input");
 
-    let seeds: Vec<_> = contents
-        .lines()
-        .next()
-        .unwrap()
-        .strip_prefix("seeds:")
-        .unwrap()
-        .split_ascii_whitespace()
-        .map(|s| s.parse::<usize>().unwrap())
-        .collect();
+    // `--to <category>` follows the maps up to another category than location
+    let mut args: Vec<String> = std::env::args().collect();
+    let mut target = "location".to_string();
+    if let Some(i) = args.iter().position(|a| a == "--to") {
+        if i + 1 >= args.len() {
+            eprintln!("Missing value for --to");
+            std::process::exit(1);
+        }
+        target = args.remove(i + 1);
+        args.remove(i);
+    }
 
+    // `--strict` refuses to solve almanacs with overlapping or malformed ranges
+    let strict = args.iter().any(|a| a == "--strict");
+    args.retain(|a| a != "--strict");
+
+    let almanac = parse_almanac(contents).unwrap_or_else(|e| {
+        eprintln!("Invalid almanac: {e}");
+        std::process::exit(1);
+    });
+    let issues = almanac.validate();
+    for issue in issues.iter() {
+        eprintln!("{}: {issue}", if strict { "Error" } else { "Warning" });
+    }
+    if strict && !issues.is_empty() {
+        std::process::exit(1);
+    }
+    let seeds = &almanac.seeds;
     debug!("seeds: {:?}", seeds);
 
-    let mut line_it = contents.lines();
-    line_it.next(); // seeds
-    line_it.next(); //empty line
+    let maps = almanac.maps_to("seed", &target).unwrap_or_else(|e| {
+        eprintln!("Invalid almanac: {e}");
+        std::process::exit(1);
+    });
 
-    let mut maps: Maps = Vec::new();
+    let range_maps: Vec<_> = maps.iter().map(|m| RangeMap::new(m)).collect();
+    let seed_to_location = compose_all(&range_maps);
 
-    for i in 0..6 {
-        let mut rmap = Vec::new();
-        line_it.next(); // map name
-        let mut line = line_it.next().unwrap(); //first range
-        while !line.is_empty() {
-            let range: Vec<_> = line
-                .split_ascii_whitespace()
-                .map(|s| s.parse::<usize>().unwrap())
-                .collect();
-            rmap.push(range);
-            line = line_it.next().unwrap();
-        }
-        debug!("map {}: {:?}", i + 1, rmap);
-        maps.push(rmap);
-    }
-
-    // last map
-    let mut rmap = Vec::new();
-    line_it.next(); // map name
-    let mut wline = line_it.next(); //first range
-    while wline.is_some() {
-        let line = wline.unwrap();
-        let range: Vec<_> = line
-            .split_ascii_whitespace()
-            .map(|s| s.parse::<usize>().unwrap())
-            .collect();
-        rmap.push(range);
-        wline = line_it.next();
+    let seed_ranges: Vec<_> = seeds
+        .chunks_exact(2)
+        .map(|p| Interval::new(p[0], p[0].saturating_add(p[1])))
+        .collect();
+    debug!("seed ranges: {:?}", seed_ranges);
+
+    match args.get(1).map(|a| a.as_str()) {
+        // `day5 compose` prints the composed seed-to-location map
+        Some("compose") => {
+            print!("{seed_to_location}");
+            return;
+        }
+        // `day5 preimage a b` prints the seed ranges with locations in a..b
+        Some("preimage") => {
+            let bound = |i: usize| args.get(i).and_then(|a| a.parse::<usize>().ok());
+            match (bound(2), bound(3)) {
+                (Some(a), Some(b)) => {
+                    for iv in preimage_all(&range_maps, Interval::new(a, b)) {
+                        println!("{}..{}", iv.start, iv.end);
+                    }
+                }
+                _ => {
+                    eprintln!("Usage: day5 preimage <a> <b>");
+                    std::process::exit(1);
+                }
+            }
+            return;
+        }
+        // `day5 search` finds the lowest location of part 2 by going backwards
+        Some("search") => {
+            match lowest_location(&range_maps, &seed_ranges) {
+                Some(loc) => println!("The new lowest {target} is {loc}"),
+                None => println!("No seed range has a location"),
+            }
+            return;
+        }
+        _ => (),
     }
-    debug!("map 7: {:?}", rmap);
-    maps.push(rmap);
 
-    let locations: Vec<_> = seeds.iter().map(|s| compose_maps(&maps, s)).collect();
+    let locations: Vec<_> = seeds.iter().map(|s| seed_to_location.get(*s)).collect();
     debug!("locations: {:?}", locations);
 
-    println!("The lowest location is {}", locations.iter().min().unwrap());
+    println!("The lowest {target} is {}", locations.iter().min().unwrap());
 
     // SECOND PART
 
-    let mut loc_min = usize::MAX;
-    for p in seeds.chunks_exact(2) {
-        let start = p[0];
-        let len = p[1];
-        debug!("seed range {start}/{len}");
-        let mut seed = start;
-        while seed < start + len {
-            debug!("iter");
-            let (loc, jump) = compose_maps_and_jump(&maps, &seed);
-            if loc < loc_min {
-                loc_min = loc;
+    let location_ranges = map_intervals(&range_maps, seed_ranges);
+    debug!("location ranges: {:?}", location_ranges);
+
+    println!(
+        "The new lowest {target} is {}",
+        location_ranges.iter().map(|iv| iv.start).min().unwrap()
+    );
+}
+
+fn parse_almanac(contents: &str) -> Result<Almanac, AlmanacError> {
+    let mut line_it = contents.lines().enumerate().map(|(i, l)| (i + 1, l));
+
+    let seeds = line_it
+        .next()
+        .and_then(|(_, l)| l.strip_prefix("seeds:"))
+        .ok_or(AlmanacError::MissingSeeds)?;
+    let seeds = seeds
+        .split_ascii_whitespace()
+        .map(|s| s.parse::<usize>())
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|_| AlmanacError::InvalidRange {
+            line: 1,
+            text: seeds.to_string(),
+        })?;
+
+    let mut maps: Vec<CategoryMap> = Vec::new();
+    for (n, line) in line_it {
+        if line.is_empty() {
+            continue;
+        }
+        if let Some(name) = line.strip_suffix(" map:") {
+            let (from, to) =
+                name.split_once("-to-")
+                    .ok_or_else(|| AlmanacError::InvalidHeader {
+                        line: n,
+                        text: line.to_string(),
+                    })?;
+            if maps.iter().any(|m| m.from == from) {
+                return Err(AlmanacError::DuplicateMap {
+                    line: n,
+                    from: from.to_string(),
+                });
+            }
+            debug!("line {n}: {from} to {to}");
+            maps.push(CategoryMap {
+                from: from.to_string(),
+                to: to.to_string(),
+                ranges: Vec::new(),
+                lines: Vec::new(),
+            });
+        } else {
+            let invalid_range = || AlmanacError::InvalidRange {
+                line: n,
+                text: line.to_string(),
+            };
+            let range: Vec<_> = line
+                .split_ascii_whitespace()
+                .map(|s| s.parse::<usize>())
+                .collect::<Result<_, _>>()
+                .map_err(|_| invalid_range())?;
+            if range.len() != 3 {
+                return Err(invalid_range());
+            }
+            // ranges before any header have no map to go to
+            let map = maps.last_mut().ok_or_else(invalid_range)?;
+            map.ranges.push(range);
+            map.lines.push(n);
+        }
+    }
+
+    Ok(Almanac { seeds, maps })
+}
+
+impl Almanac {
+    // maps to apply in order to go from one category to another
+    fn maps_to(&self, from: &str, to: &str) -> Result<Maps, AlmanacError> {
+        let is_category = |c: &str| self.maps.iter().any(|m| m.from == c || m.to == c);
+        for c in [from, to] {
+            if !is_category(c) {
+                return Err(AlmanacError::UnknownCategory(c.to_string()));
+            }
+        }
+        let mut path = vec![from];
+        let mut maps = Vec::new();
+        let mut category = from;
+        while category != to {
+            let map = self
+                .maps
+                .iter()
+                .find(|m| m.from == category)
+                .ok_or_else(|| AlmanacError::NoPath {
+                    from: from.to_string(),
+                    to: to.to_string(),
+                })?;
+            category = &map.to;
+            if let Some(i) = path.iter().position(|c| *c == category) {
+                let mut cycle: Vec<_> = path[i..].iter().map(|c| c.to_string()).collect();
+                cycle.push(category.to_string());
+                return Err(AlmanacError::Cycle(cycle));
+            }
+            path.push(category);
+            maps.push(map.ranges.clone());
+        }
+        debug!("path: {}", path.join(" -> "));
+        Ok(maps)
+    }
+}
+
+impl Almanac {
+    // zero-length ranges, ranges overflowing a usize and overlapping source
+    // ranges in each map
+    fn validate(&self) -> Vec<RangeIssue> {
+        let mut issues = Vec::new();
+        for map in self.maps.iter() {
+            let name = format!("{}-to-{}", map.from, map.to);
+            let issue = |line, kind| RangeIssue {
+                map: name.clone(),
+                line,
+                kind,
+            };
+            // (src, end, line) of well-formed ranges
+            let mut sources = Vec::new();
+            for (r, &line) in map.ranges.iter().zip(map.lines.iter()) {
+                if r[2] == 0 {
+                    issues.push(issue(line, RangeIssueKind::ZeroLength));
+                } else if r[0].checked_add(r[2]).is_none() || r[1].checked_add(r[2]).is_none() {
+                    issues.push(issue(line, RangeIssueKind::Overflow));
+                } else {
+                    sources.push((r[1], r[1] + r[2], line));
+                }
+            }
+            sources.sort();
+            // range reaching furthest among those seen so far
+            let mut furthest: Option<(usize, usize, usize)> = None;
+            for src in sources {
+                match furthest {
+                    Some(f) if src.0 < f.1 => {
+                        let (first, second) = (f.2.min(src.2), f.2.max(src.2));
+                        issues.push(issue(second, RangeIssueKind::Overlap(first)));
+                        if src.1 > f.1 {
+                            furthest = Some(src);
+                        }
+                    }
+                    _ => furthest = Some(src),
+                }
             }
-            seed += jump;
         }
+        issues.sort_by_key(|i| i.line);
+        issues
     }
+}
 
-    println!("The new lowest location is {}", loc_min);
+impl fmt::Display for RangeIssue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} map, line {}: ", self.map, self.line)?;
+        match self.kind {
+            RangeIssueKind::ZeroLength => write!(f, "range of length 0"),
+            RangeIssueKind::Overflow => write!(f, "range end overflows"),
+            RangeIssueKind::Overlap(line) => {
+                write!(f, "source range overlaps the one at line {line}")
+            }
+        }
+    }
+}
+
+impl fmt::Display for AlmanacError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AlmanacError::MissingSeeds => write!(f, "first line should list seeds"),
+            AlmanacError::InvalidHeader { line, text } => {
+                write!(f, "line {line}: expected \"X-to-Y map:\", got \"{text}\"")
+            }
+            AlmanacError::InvalidRange { line, text } => {
+                write!(f, "line {line}: expected \"dest src len\", got \"{text}\"")
+            }
+            AlmanacError::DuplicateMap { line, from } => {
+                write!(f, "line {line}: second map from category {from}")
+            }
+            AlmanacError::UnknownCategory(c) => write!(f, "no map involves category {c}"),
+            AlmanacError::NoPath { from, to } => {
+                write!(f, "no sequence of maps goes from {from} to {to}")
+            }
+            AlmanacError::Cycle(cycle) => {
+                write!(f, "maps go round in a cycle {}", cycle.join(" -> "))
+            }
+        }
+    }
 }
 
+// reference implementation, scanning every map
+#[cfg(test)]
 fn compose_maps(maps: &Maps, seed: &usize) -> usize {
     let mut i = *seed;
     debug!("=== Compose maps ===");
@@ -99,19 +366,584 @@ fn compose_maps(maps: &Maps, seed: &usize) -> usize {
     i
 }
 
-fn compose_maps_and_jump(maps: &Maps, seed: &usize) -> (usize, usize) {
-    let mut i = *seed;
-    let mut range_skips = Vec::new();
-    debug!("=== Compose maps and jump===");
-    for map in maps.iter() {
-        for range in map.iter() {
-            if i >= range[1] && i < range[1] + range[2] {
-                range_skips.push(range[1] + range[2] - i);
-                i = range[0] + i - range[1];
-                debug!("range {}", range[0]);
-                break;
+impl Interval {
+    fn new(start: usize, end: usize) -> Interval {
+        Interval { start, end }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.start >= self.end
+    }
+}
+
+impl Segment {
+    fn src_end(&self) -> usize {
+        self.src + self.len
+    }
+
+    fn apply(&self, i: usize) -> usize {
+        self.dest + i - self.src
+    }
+}
+
+impl RangeMap {
+    // builds the map from "dest src len" triples; where source ranges overlap,
+    // the first range in the list wins, and ranges overflowing a usize are cut
+    fn new(ranges: &[Vec<usize>]) -> RangeMap {
+        let mut map = RangeMap {
+            segments: Vec::new(),
+        };
+        for r in ranges {
+            let (dest, src) = (r[0], r[1]);
+            let len = r[2].min(usize::MAX - src).min(usize::MAX - dest);
+            // only keep the parts not covered by previous ranges
+            let free: Vec<_> = map
+                .split(Interval::new(src, src + len))
+                .into_iter()
+                .filter(|(_, seg)| seg.is_none())
+                .map(|(piece, _)| Segment {
+                    dest: dest + piece.start - src,
+                    src: piece.start,
+                    len: piece.end - piece.start,
+                })
+                .collect();
+            map.segments.extend(free);
+            map.segments.sort_by_key(|s| s.src);
+        }
+        map
+    }
+
+    // splits an interval into consecutive pieces, each of which either lies
+    // within a single segment or is not covered by any segment
+    fn split(&self, iv: Interval) -> Vec<(Interval, Option<Segment>)> {
+        let mut pieces = Vec::new();
+        let mut cur = iv.start;
+        // first segment which ends after the start of the interval
+        let mut idx = self.segments.partition_point(|s| s.src_end() <= cur);
+        while cur < iv.end {
+            match self.segments.get(idx) {
+                Some(s) if s.src <= cur => {
+                    let end = iv.end.min(s.src_end());
+                    pieces.push((Interval::new(cur, end), Some(*s)));
+                    cur = end;
+                    idx += 1;
+                }
+                Some(s) => {
+                    let end = iv.end.min(s.src);
+                    pieces.push((Interval::new(cur, end), None));
+                    cur = end;
+                }
+                None => {
+                    pieces.push((Interval::new(cur, iv.end), None));
+                    cur = iv.end;
+                }
+            }
+        }
+        pieces
+    }
+
+    // image of an interval, as a union of intervals
+    fn map_interval(&self, iv: Interval) -> Vec<Interval> {
+        self.split(iv)
+            .into_iter()
+            .map(|(piece, seg)| match seg {
+                Some(s) => Interval::new(
+                    s.apply(piece.start),
+                    s.apply(piece.start) + piece.end - piece.start,
+                ),
+                None => piece,
+            })
+            .collect()
+    }
+}
+
+impl RangeMap {
+    // image of a single number, found by binary search
+    fn get(&self, i: usize) -> usize {
+        let idx = self.segments.partition_point(|s| s.src_end() <= i);
+        match self.segments.get(idx) {
+            Some(s) if s.src <= i => s.apply(i),
+            _ => i,
+        }
+    }
+
+    // map equivalent to applying self then next; segments mapping numbers to
+    // themselves are dropped and contiguous segments are merged
+    fn then(&self, next: &RangeMap) -> RangeMap {
+        let mut segments: Vec<Segment> = Vec::new();
+        for (piece, seg) in self.split(Interval::new(0, usize::MAX)) {
+            let image_start = seg.map_or(piece.start, |s| s.apply(piece.start));
+            let image = Interval::new(image_start, image_start + (piece.end - piece.start));
+            for (sub, next_seg) in next.split(image) {
+                let src = piece.start + (sub.start - image_start);
+                let dest = next_seg.map_or(sub.start, |s| s.apply(sub.start));
+                let len = sub.end - sub.start;
+                if src == dest {
+                    continue;
+                }
+                match segments.last_mut() {
+                    Some(last) if last.src_end() == src && last.dest + last.len == dest => {
+                        last.len += len
+                    }
+                    _ => segments.push(Segment { dest, src, len }),
+                }
             }
         }
+        RangeMap { segments }
+    }
+}
+
+// prints the map in the "dest src len" format of the almanac
+impl fmt::Display for RangeMap {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for s in self.segments.iter() {
+            writeln!(f, "{} {} {}", s.dest, s.src, s.len)?;
+        }
+        Ok(())
+    }
+}
+
+impl RangeMap {
+    // numbers whose image lies in the interval, as a union of intervals; maps
+    // need not be injective, so this includes both segments whose destination
+    // meets the interval and numbers of the interval not covered by any segment
+    fn preimage(&self, iv: Interval) -> Vec<Interval> {
+        let mut intervals: Vec<_> = self
+            .segments
+            .iter()
+            .filter_map(|s| {
+                let start = iv.start.max(s.dest);
+                let end = iv.end.min(s.dest + s.len);
+                if start < end {
+                    Some(Interval::new(s.src + start - s.dest, s.src + end - s.dest))
+                } else {
+                    None
+                }
+            })
+            .collect();
+        intervals.extend(
+            self.split(iv)
+                .into_iter()
+                .filter(|(_, seg)| seg.is_none())
+                .map(|(piece, _)| piece),
+        );
+        normalize(intervals)
+    }
+}
+
+// seeds whose location lies in the interval, going backwards through all maps
+fn preimage_all(maps: &[RangeMap], iv: Interval) -> Vec<Interval> {
+    maps.iter().rev().fold(vec![iv], |ivs, map| {
+        normalize(ivs.into_iter().flat_map(|iv| map.preimage(iv)).collect())
+    })
+}
+
+// whether two unions of sorted disjoint intervals meet
+fn meet(a: &[Interval], b: &[Interval]) -> bool {
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() && j < b.len() {
+        if a[i].start.max(b[j].start) < a[i].end.min(b[j].end) {
+            return true;
+        }
+        if a[i].end < b[j].end {
+            i += 1;
+        } else {
+            j += 1;
+        }
+    }
+    false
+}
+
+// lowest location of a seed in the given ranges, searching upward from location 0:
+// the window 0..hi is doubled until some seed lands in it, then the lowest
+// location is found by binary search within the window
+fn lowest_location(maps: &[RangeMap], seed_ranges: &[Interval]) -> Option<usize> {
+    let seeds = normalize(seed_ranges.to_vec());
+    let hits = |hi: usize| meet(&preimage_all(maps, Interval::new(0, hi)), &seeds);
+    let mut hi = 1;
+    while !hits(hi) {
+        if hi == usize::MAX {
+            return None;
+        }
+        hi = hi.saturating_mul(2);
+    }
+    // the lowest location is in lo..hi, with hits(lo) false and hits(hi) true
+    let mut lo = hi / 2;
+    while hi - lo > 1 {
+        let mid = lo + (hi - lo) / 2;
+        if hits(mid) {
+            hi = mid;
+        } else {
+            lo = mid;
+        }
+    }
+    Some(lo)
+}
+
+// single map equivalent to applying all maps in order
+fn compose_all(maps: &[RangeMap]) -> RangeMap {
+    maps.iter().fold(
+        RangeMap {
+            segments: Vec::new(),
+        },
+        |acc, m| acc.then(m),
+    )
+}
+
+// sorts intervals and merges those which overlap or touch
+fn normalize(mut intervals: Vec<Interval>) -> Vec<Interval> {
+    intervals.retain(|iv| !iv.is_empty());
+    intervals.sort();
+    let mut merged: Vec<Interval> = Vec::new();
+    for iv in intervals {
+        match merged.last_mut() {
+            Some(last) if iv.start <= last.end => last.end = last.end.max(iv.end),
+            _ => merged.push(iv),
+        }
+    }
+    merged
+}
+
+// image of a union of intervals through all maps
+fn map_intervals(maps: &[RangeMap], intervals: Vec<Interval>) -> Vec<Interval> {
+    maps.iter().fold(normalize(intervals), |ivs, map| {
+        normalize(
+            ivs.into_iter()
+                .flat_map(|iv| map.map_interval(iv))
+                .collect(),
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    const EXAMPLE: &str = "seeds: 79 14 55 13
+
+seed-to-soil map:
+50 98 2
+52 50 48
+
+soil-to-fertilizer map:
+0 15 37
+37 52 2
+39 0 15
+
+fertilizer-to-water map:
+49 53 8
+0 11 42
+42 0 7
+57 7 4
+
+water-to-light map:
+88 18 7
+18 25 70
+
+light-to-temperature map:
+45 77 23
+81 45 19
+68 64 13
+
+temperature-to-humidity map:
+0 69 1
+1 0 69
+
+humidity-to-location map:
+60 56 37
+56 93 4";
+
+    fn example() -> (Vec<usize>, Maps) {
+        let almanac = parse_almanac(EXAMPLE).unwrap();
+        let maps = almanac.maps_to("seed", "location").unwrap();
+        (almanac.seeds, maps)
+    }
+
+    #[test]
+    fn test_parse_almanac() {
+        let almanac = parse_almanac(EXAMPLE).unwrap();
+        assert_eq!(almanac.seeds, [79, 14, 55, 13]);
+        assert_eq!(almanac.maps.len(), 7);
+        assert_eq!(almanac.maps[6].from, "humidity");
+        assert_eq!(almanac.maps[6].to, "location");
+        assert_eq!(almanac.maps[6].ranges, [[60, 56, 37], [56, 93, 4]]);
+        let to_humidity = almanac.maps_to("seed", "humidity").unwrap();
+        assert_eq!(to_humidity.len(), 6);
+        assert_eq!(almanac.maps_to("soil", "water").unwrap().len(), 2);
+        assert_eq!(almanac.maps_to("seed", "seed").unwrap().len(), 0);
+    }
+
+    #[test]
+    fn test_parse_almanac_unordered() {
+        // sections can come in any order
+        let almanac = parse_almanac(
+            "seeds: 1 2\n\n\
+             soil-to-water map:\n5 0 10\n\n\
+             seed-to-soil map:\n0 5 10\n",
+        )
+        .unwrap();
+        assert_eq!(
+            almanac.maps_to("seed", "water").unwrap(),
+            [vec![vec![0, 5, 10]], vec![vec![5, 0, 10]]]
+        );
+    }
+
+    #[test]
+    fn test_parse_almanac_errors() {
+        assert_eq!(
+            parse_almanac("seed: 1 2\n"),
+            Err(AlmanacError::MissingSeeds)
+        );
+        assert_eq!(
+            parse_almanac("seeds: 1 2\n\nseed-soil map:\n"),
+            Err(AlmanacError::InvalidHeader {
+                line: 3,
+                text: "seed-soil map:".to_string()
+            })
+        );
+        assert_eq!(
+            parse_almanac("seeds: 1 2\n\nseed-to-soil map:\n1 2\n"),
+            Err(AlmanacError::InvalidRange {
+                line: 4,
+                text: "1 2".to_string()
+            })
+        );
+        assert_eq!(
+            parse_almanac("seeds: 1\n\nseed-to-soil map:\n\nseed-to-water map:\n"),
+            Err(AlmanacError::DuplicateMap {
+                line: 5,
+                from: "seed".to_string()
+            })
+        );
+        let almanac = parse_almanac(EXAMPLE).unwrap();
+        assert_eq!(
+            almanac.maps_to("seed", "weather"),
+            Err(AlmanacError::UnknownCategory("weather".to_string()))
+        );
+        assert_eq!(
+            almanac.maps_to("location", "seed"),
+            Err(AlmanacError::NoPath {
+                from: "location".to_string(),
+                to: "seed".to_string()
+            })
+        );
+        let cyclic = parse_almanac(
+            "seeds: 1\n\n\
+             seed-to-soil map:\n\n\
+             soil-to-water map:\n\n\
+             water-to-soil map:\n\n\
+             location-to-seed map:\n",
+        )
+        .unwrap();
+        assert_eq!(
+            cyclic.maps_to("seed", "location"),
+            Err(AlmanacError::Cycle(vec![
+                "soil".to_string(),
+                "water".to_string(),
+                "soil".to_string()
+            ]))
+        );
+    }
+
+    #[test]
+    fn test_validate() {
+        assert_eq!(parse_almanac(EXAMPLE).unwrap().validate(), []);
+        let almanac = parse_almanac(&format!(
+            "seeds: 1\n\n\
+             seed-to-soil map:\n\
+             0 10 5\n\
+             100 12 2\n\
+             50 0 0\n\
+             1 {} 2\n\
+             200 0 11\n",
+            usize::MAX - 1
+        ))
+        .unwrap();
+        let issue = |line, kind| RangeIssue {
+            map: "seed-to-soil".to_string(),
+            line,
+            kind,
+        };
+        assert_eq!(
+            almanac.validate(),
+            [
+                issue(5, RangeIssueKind::Overlap(4)),
+                issue(6, RangeIssueKind::ZeroLength),
+                issue(7, RangeIssueKind::Overflow),
+                issue(8, RangeIssueKind::Overlap(4)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_range_map_first_match() {
+        // 10..15 is covered by the first range, 0..10 by the last one
+        let map = RangeMap::new(&[
+            vec![0, 10, 5],
+            vec![100, 12, 2],
+            vec![50, 0, 0],
+            vec![1, usize::MAX - 1, 2],
+            vec![200, 0, 11],
+        ]);
+        assert_eq!(map.get(0), 200);
+        assert_eq!(map.get(9), 209);
+        assert_eq!(map.get(10), 0);
+        assert_eq!(map.get(12), 2);
+        assert_eq!(map.get(14), 4);
+        assert_eq!(map.get(15), 15);
+        assert_eq!(map.get(usize::MAX - 1), 1);
+    }
+
+    #[test]
+    fn test_split() {
+        let map = RangeMap::new(&[vec![50, 98, 2], vec![52, 50, 48]]);
+        let pieces = map.split(Interval::new(40, 110));
+        assert_eq!(
+            pieces,
+            [
+                (Interval::new(40, 50), None),
+                (Interval::new(50, 98), Some(map.segments[0])),
+                (Interval::new(98, 100), Some(map.segments[1])),
+                (Interval::new(100, 110), None),
+            ]
+        );
+        assert_eq!(
+            map.map_interval(Interval::new(97, 101)),
+            [
+                Interval::new(99, 100),
+                Interval::new(50, 52),
+                Interval::new(100, 101)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_compose() {
+        let (seeds, maps) = example();
+        let range_maps: Vec<_> = maps.iter().map(|m| RangeMap::new(m)).collect();
+        let composed = compose_all(&range_maps);
+        for seed in 0..120 {
+            assert_eq!(
+                composed.get(seed),
+                compose_maps(&maps, &seed),
+                "seed {seed}"
+            );
+        }
+        assert_eq!(
+            seeds.iter().map(|s| composed.get(*s)).collect::<Vec<_>>(),
+            [82, 43, 86, 35]
+        );
+        // segments are sorted, disjoint and do not map numbers to themselves
+        for w in composed.segments.windows(2) {
+            assert!(w[0].src_end() <= w[1].src);
+        }
+        assert!(composed.segments.iter().all(|s| s.src != s.dest));
+        // the printed map can be parsed back
+        let printed: Vec<Vec<usize>> = composed
+            .to_string()
+            .lines()
+            .map(|l| l.split(' ').map(|n| n.parse().unwrap()).collect())
+            .collect();
+        assert_eq!(RangeMap::new(&printed), composed);
+    }
+
+    #[test]
+    fn test_compose_merges_segments() {
+        let first = RangeMap::new(&[vec![100, 0, 10]]);
+        let second = RangeMap::new(&[vec![200, 100, 5], vec![205, 105, 5]]);
+        // both halves of 0..10 and of 100..110 (unmapped by the first map)
+        // end up in a single segment each
+        assert_eq!(
+            first.then(&second).segments,
+            [
+                Segment {
+                    dest: 200,
+                    src: 0,
+                    len: 10
+                },
+                Segment {
+                    dest: 200,
+                    src: 100,
+                    len: 10
+                }
+            ]
+        );
+        // 0..10 goes back to itself and is dropped, while 100..110 (unmapped
+        // by the first map) is sent to 0..10
+        let back = RangeMap::new(&[vec![0, 100, 10]]);
+        assert_eq!(
+            first.then(&back).segments,
+            [Segment {
+                dest: 0,
+                src: 100,
+                len: 10
+            }]
+        );
+    }
+
+    #[test]
+    fn test_preimage() {
+        let map = RangeMap::new(&[vec![50, 98, 2], vec![52, 50, 48]]);
+        // 50..52 is reached from 98..100, and 50 and 51 are not mapped to themselves
+        assert_eq!(
+            map.preimage(Interval::new(50, 52)),
+            [Interval::new(98, 100)]
+        );
+        // 98 and 99 are reached from 96 and 97, and 98..100 is mapped elsewhere
+        assert_eq!(
+            map.preimage(Interval::new(98, 100)),
+            [Interval::new(96, 98)]
+        );
+        // 10..20 is mapped to itself
+        assert_eq!(map.preimage(Interval::new(10, 20)), [Interval::new(10, 20)]);
+    }
+
+    #[test]
+    fn test_preimage_all() {
+        let (seeds, maps) = example();
+        let range_maps: Vec<_> = maps.iter().map(|m| RangeMap::new(m)).collect();
+        for seed in seeds {
+            let loc = compose_maps(&maps, &seed);
+            let seeds_back = preimage_all(&range_maps, Interval::new(loc, loc + 1));
+            assert!(seeds_back
+                .iter()
+                .any(|iv| iv.start <= seed && seed < iv.end));
+        }
+        let seeds_back = preimage_all(&range_maps, Interval::new(0, 100));
+        for seed in 0..150 {
+            let in_preimage = seeds_back
+                .iter()
+                .any(|iv| iv.start <= seed && seed < iv.end);
+            assert_eq!(in_preimage, compose_maps(&maps, &seed) < 100, "seed {seed}");
+        }
+    }
+
+    #[test]
+    fn test_lowest_location() {
+        let (seeds, maps) = example();
+        let range_maps: Vec<_> = maps.iter().map(|m| RangeMap::new(m)).collect();
+        let seed_ranges: Vec<_> = seeds
+            .chunks_exact(2)
+            .map(|p| Interval::new(p[0], p[0] + p[1]))
+            .collect();
+        assert_eq!(lowest_location(&range_maps, &seed_ranges), Some(46));
+        let single_seeds: Vec<_> = seeds.iter().map(|s| Interval::new(*s, s + 1)).collect();
+        assert_eq!(lowest_location(&range_maps, &single_seeds), Some(35));
+        assert_eq!(lowest_location(&range_maps, &[]), None);
+    }
+
+    #[test]
+    fn test_example() {
+        let (seeds, maps) = example();
+        let lowest = seeds.iter().map(|s| compose_maps(&maps, s)).min();
+        assert_eq!(lowest, Some(35));
+        let range_maps: Vec<_> = maps.iter().map(|m| RangeMap::new(m)).collect();
+        let seed_ranges = seeds
+            .chunks_exact(2)
+            .map(|p| Interval::new(p[0], p[0] + p[1]))
+            .collect();
+        let locations = map_intervals(&range_maps, seed_ranges);
+        assert_eq!(locations[0].start, 46);
     }
-    (i, *range_skips.iter().min().unwrap())
 }
@@ -1,23 +1,203 @@
 fn main() {
-    let n1 = n_ways_win(47, 207);
-    let n2 = n_ways_win(84, 1394);
-    let n3 = n_ways_win(74, 1209);
-    let n4 = n_ways_win(67, 1014);
-    let prod = n1*n2*n3*n4;
+    let contents = include_str!("../input");
+
+    let races = parse_races(contents);
+    for (i, (time, record)) in races.iter().enumerate() {
+        let n = n_ways_win_exact(*time, *record);
+        println!(
+            "Race {}: time {time}, record {record}, {n} ways to win",
+            i + 1
+        );
+    }
+    let prod: u128 = races.iter().map(|r| n_ways_win_exact(r.0, r.1)).product();
 
     println!("The product of n_i's is {prod}");
 
-    let n = n_ways_win(47847467, 207139412091014);
+    let (time, record) = parse_kerned_race(contents);
+    let n = n_ways_win_exact(time, record);
 
+    println!("Kerned race: time {time}, record {record}");
     println!("The new n is {n}");
 }
 
-fn n_ways_win(time: usize, record: usize) -> usize {
+// numbers following the "Time:" and "Distance:" labels, as (time, record) pairs
+fn parse_races(contents: &str) -> Vec<(u128, u128)> {
+    let mut lines = contents.lines();
+    let times = parse_numbers(lines.next().unwrap(), "Time:");
+    let records = parse_numbers(lines.next().unwrap(), "Distance:");
+    assert_eq!(
+        times.len(),
+        records.len(),
+        "Each race needs a time and a record"
+    );
+    times.into_iter().zip(records).collect()
+}
+
+fn parse_numbers(line: &str, label: &str) -> Vec<u128> {
+    line.strip_prefix(label)
+        .unwrap_or_else(|| panic!("Expected a line starting with {label}"))
+        .split_ascii_whitespace()
+        .map(|n| n.parse().unwrap())
+        .collect()
+}
+
+// single race obtained by ignoring spaces between numbers
+fn parse_kerned_race(contents: &str) -> (u128, u128) {
+    let mut lines = contents.lines();
+    let time = parse_kerned_number(lines.next().unwrap(), "Time:");
+    let record = parse_kerned_number(lines.next().unwrap(), "Distance:");
+    (time, record)
+}
+
+fn parse_kerned_number(line: &str, label: &str) -> u128 {
+    line.strip_prefix(label)
+        .unwrap_or_else(|| panic!("Expected a line starting with {label}"))
+        .chars()
+        .filter(|c| !c.is_ascii_whitespace())
+        .collect::<String>()
+        .parse()
+        .unwrap()
+}
+
+// reference implementation, trying every hold time
+#[cfg(test)]
+fn n_ways_win(time: u128, record: u128) -> u128 {
     let mut n = 0;
 
     for i in 1..time {
-        let d = i*(time-i);
-        if d > record { n += 1; }
+        let d = i * (time - i);
+        if d > record {
+            n += 1;
+        }
     }
     n
-}
\ No newline at end of file
+}
+
+// The distance i * (time - i) is increasing for i in 0..=time/2 and symmetric
+// around time/2, so winning hold times form the range lo..=time - lo where lo
+// is the smallest integer strictly above the root (time - sqrt(time^2 - 4 * record)) / 2.
+fn n_ways_win_exact(time: u128, record: u128) -> u128 {
+    let half = time / 2;
+    if !wins(time, record, half) {
+        return 0;
+    }
+    let mut lo = match time
+        .checked_mul(time)
+        .and_then(|t2| t2.checked_sub(record.checked_mul(4)?))
+    {
+        // isqrt(d) is within 1 of sqrt(d), so this is off by at most one
+        Some(d) => (time - isqrt(d)) / 2,
+        // time^2 does not fit in a u128: binary search for lo in 0..=half instead
+        None => {
+            let (mut a, mut b) = (0, half);
+            while a < b {
+                let mid = a + (b - a) / 2;
+                if wins(time, record, mid) {
+                    b = mid;
+                } else {
+                    a = mid + 1;
+                }
+            }
+            a
+        }
+    };
+    while !wins(time, record, lo) {
+        lo += 1;
+    }
+    while lo > 0 && wins(time, record, lo - 1) {
+        lo -= 1;
+    }
+    time - 2 * lo + 1
+}
+
+// whether holding the button for i ms (with i <= time) beats the record,
+// an overflowing distance being larger than any record
+fn wins(time: u128, record: u128, i: u128) -> bool {
+    i.checked_mul(time - i).is_none_or(|d| d > record)
+}
+
+// largest x such that x * x <= n, using Newton's method
+fn isqrt(n: u128) -> u128 {
+    if n < 2 {
+        return n;
+    }
+    // initial guess 2^ceil(bits/2) is above the root, and iterates decrease towards it
+    let mut x = 1u128 << ((128 - n.leading_zeros()).div_ceil(2));
+    loop {
+        let y = (x + n / x) / 2;
+        if y >= x {
+            return x;
+        }
+        x = y;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    const EXAMPLE: &str = "Time:      7  15   30\nDistance:  9  40  200\n";
+
+    #[test]
+    fn test_parse_races() {
+        assert_eq!(parse_races(EXAMPLE), [(7, 9), (15, 40), (30, 200)]);
+        assert_eq!(parse_kerned_race(EXAMPLE), (71530, 940200));
+    }
+
+    #[test]
+    fn test_n_ways_win() {
+        let prod: u128 = parse_races(EXAMPLE)
+            .iter()
+            .map(|r| n_ways_win(r.0, r.1))
+            .product();
+        assert_eq!(prod, 288);
+        assert_eq!(n_ways_win(71530, 940200), 71503);
+    }
+
+    #[test]
+    fn test_isqrt() {
+        for n in 0..10_000u128 {
+            let r = isqrt(n);
+            assert!(r * r <= n && (r + 1) * (r + 1) > n, "isqrt({n}) = {r}");
+        }
+        assert_eq!(isqrt(u128::MAX), u64::MAX as u128);
+        assert_eq!(isqrt((1 << 100) - 1), (1 << 50) - 1);
+        assert_eq!(isqrt(1 << 100), 1 << 50);
+    }
+
+    #[test]
+    fn test_n_ways_win_exact() {
+        for time in 0..120 {
+            for record in 0..=time * time / 4 + 1 {
+                assert_eq!(
+                    n_ways_win_exact(time, record),
+                    n_ways_win(time, record),
+                    "time {time}, record {record}"
+                );
+            }
+        }
+        assert_eq!(n_ways_win_exact(71530, 940200), 71503);
+        assert_eq!(n_ways_win_exact(47847467, 207139412091014), 38220708);
+    }
+
+    #[test]
+    fn test_n_ways_win_exact_perfect_square() {
+        // roots 4 and 6 are integers and only tie the record
+        assert_eq!(n_ways_win_exact(10, 24), 1);
+        // roots 3 and 7
+        assert_eq!(n_ways_win_exact(10, 21), 3);
+        // double root 5
+        assert_eq!(n_ways_win_exact(10, 25), 0);
+    }
+
+    #[test]
+    fn test_n_ways_win_exact_large() {
+        assert_eq!(n_ways_win_exact(u128::MAX, 0), u128::MAX - 1);
+        assert_eq!(n_ways_win_exact(u128::MAX, u128::MAX), u128::MAX - 3);
+        // time^2 just fits, and the record ties the maximal distance
+        let time = 1u128 << 63;
+        assert_eq!(n_ways_win_exact(time, (time / 2) * (time / 2)), 0);
+        assert_eq!(n_ways_win_exact(time, (time / 2) * (time / 2) - 1), 1);
+    }
+}
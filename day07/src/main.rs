@@ -1,6 +1,9 @@
 use log::debug;
 use simple_logger::SimpleLogger;
-use std::cmp::Ordering;
+use std::cmp::{Ordering, Reverse};
+use std::fmt;
+use std::str::FromStr;
+use std::time::Instant;
 use strum::IntoEnumIterator;
 use strum_macros::EnumIter;
 
@@ -24,18 +27,26 @@ enum Card {
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 enum HandType {
     Five,
+    StraightFlush,
     Four,
     Full,
+    Flush,
+    Straight,
     Three,
     Two,
     One,
     High,
 }
 
-#[derive(Debug, Copy, Clone, PartialEq, Eq)]
-struct Hand([Card; 5]);
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Hand(Vec<Card>);
 
-#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+// hand type followed by the value of each card, so that comparing keys is the
+// same as comparing hands, but the hand type is only computed once per hand
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+struct SortKey(u8, Vec<u8>);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
 struct WeightedHand {
     hand: Hand,
     weight: usize,
@@ -61,6 +72,35 @@ impl Card {
     }
 }
 
+impl Card {
+    fn to_char(self) -> char {
+        match self {
+            Card::Two => '2',
+            Card::Three => '3',
+            Card::Four => '4',
+            Card::Five => '5',
+            Card::Six => '6',
+            Card::Seven => '7',
+            Card::Eight => '8',
+            Card::Nine => '9',
+            Card::T => 'T',
+            Card::J => 'J',
+            Card::Q => 'Q',
+            Card::K => 'K',
+            Card::A => 'A',
+        }
+    }
+}
+
+impl fmt::Display for Hand {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for c in self.0.iter() {
+            write!(f, "{}", c.to_char())?;
+        }
+        Ok(())
+    }
+}
+
 impl Ord for Card {
     fn cmp(&self, other: &Self) -> Ordering {
         self.to_int().cmp(&other.to_int())
@@ -76,9 +116,12 @@ impl PartialOrd for Card {
 impl HandType {
     fn to_int(self) -> u8 {
         match self {
-            HandType::Five => 6,
-            HandType::Four => 5,
-            HandType::Full => 4,
+            HandType::Five => 9,
+            HandType::StraightFlush => 8,
+            HandType::Four => 7,
+            HandType::Full => 6,
+            HandType::Flush => 5,
+            HandType::Straight => 4,
             HandType::Three => 3,
             HandType::Two => 2,
             HandType::One => 1,
@@ -99,100 +142,61 @@ impl PartialOrd for HandType {
     }
 }
 
+// Table of hand categories from best to worst, each given by the card counts
+// it requires: a hand belongs to the first category whose counts it covers, e.g.,
+// [3, 2] is covered by three cards of a rank and two cards of another rank
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct HandRules(Vec<(HandType, Vec<usize>)>);
+
+impl HandRules {
+    fn camel_cards() -> HandRules {
+        HandRules(vec![
+            (HandType::Five, vec![5]),
+            (HandType::Four, vec![4]),
+            (HandType::Full, vec![3, 2]),
+            (HandType::Three, vec![3]),
+            (HandType::Two, vec![2, 2]),
+            (HandType::One, vec![2]),
+            (HandType::High, vec![]),
+        ])
+    }
+
+    // counts must be sorted in decreasing order
+    fn classify(&self, counts: &[usize]) -> HandType {
+        self.classify_with_wilds(counts, 0)
+    }
+}
+
+// number of cards of each rank present, in decreasing order
+fn count_ranks(cards: &[Card]) -> Vec<usize> {
+    let mut cards = cards.to_vec();
+    cards.sort();
+    let mut counts: Vec<usize> = cards
+        .chunk_by(|c, d| c == d)
+        .map(|chunk| chunk.len())
+        .collect();
+    counts.sort_by(|a, b| b.cmp(a));
+    counts
+}
+
 impl Hand {
-    fn to_handtype(mut self) -> HandType {
-        self.0.sort();
-        if self.0[0] == self.0[1] {
-            // XX***
-            if self.0[1] == self.0[2] {
-                //XXX**
-                if self.0[2] == self.0[3] {
-                    // XXXX*
-                    if self.0[3] == self.0[4] {
-                        // XXXXX
-                        HandType::Five
-                    } else {
-                        // XXXXY
-                        HandType::Four
-                    }
-                } else {
-                    // XXXY*
-                    if self.0[3] == self.0[4] {
-                        // XXXYY
-                        HandType::Full
-                    } else {
-                        // XXXYZ
-                        HandType::Three
-                    }
-                }
-            } else {
-                // XXY**
-                if self.0[2] == self.0[3] {
-                    // XXYY*
-                    if self.0[3] == self.0[4] {
-                        // XXYYY
-                        HandType::Full
-                    } else {
-                        // XXYYZ
-                        HandType::Two
-                    }
-                } else {
-                    // XXYZ*
-                    if self.0[3] == self.0[4] {
-                        // XXYZZ
-                        HandType::Two
-                    } else {
-                        // XXYZT
-                        HandType::One
-                    }
-                }
-            }
-        } else {
-            // XY***
-            if self.0[1] == self.0[2] {
-                //XYY**
-                if self.0[2] == self.0[3] {
-                    // XYYY*
-                    if self.0[3] == self.0[4] {
-                        // XYYYY
-                        HandType::Four
-                    } else {
-                        // XYYYZ
-                        HandType::Three
-                    }
-                } else {
-                    // XYYZ*
-                    if self.0[3] == self.0[4] {
-                        // XYYZZ
-                        HandType::Two
-                    } else {
-                        // XYYZT
-                        HandType::One
-                    }
-                }
-            } else {
-                // XYZ**
-                if self.0[2] == self.0[3] {
-                    // XYZZ*
-                    if self.0[3] == self.0[4] {
-                        // XYZZZ
-                        HandType::Three
-                    } else {
-                        // XYZZT
-                        HandType::One
-                    }
-                } else {
-                    // XYZT*
-                    if self.0[3] == self.0[4] {
-                        // XYZTT
-                        HandType::One
-                    } else {
-                        // XYZTU
-                        HandType::High
-                    }
-                }
-            }
-        }
+    fn counts(&self) -> Vec<usize> {
+        count_ranks(&self.0)
+    }
+
+    fn to_handtype_with(&self, rules: &HandRules) -> HandType {
+        rules.classify(&self.counts())
+    }
+
+    fn to_handtype(&self) -> HandType {
+        self.to_handtype_with(&HandRules::camel_cards())
+    }
+
+    fn sort_key_with(&self, rules: &HandRules) -> SortKey {
+        SortKey(
+            self.to_handtype_with(rules).to_int(),
+            self.0.iter().map(|c| c.to_int()).collect(),
+        )
     }
 }
 
@@ -206,11 +210,10 @@ impl Ord for Hand {
             if ht1 != ht2 {
                 ht1.to_int().cmp(&ht2.to_int())
             } else {
-                let mut i = 0;
-                while self.0[i] == other.0[i] {
-                    i += 1;
-                }
-                self.0[i].to_int().cmp(&other.0[i].to_int())
+                self.0
+                    .iter()
+                    .map(|c| c.to_int())
+                    .cmp(other.0.iter().map(|c| c.to_int()))
             }
         }
     }
@@ -234,124 +237,335 @@ impl PartialOrd for WeightedHand {
     }
 }
 
-trait ToCard {
-    fn to_card(&self) -> Card;
+// error when parsing a list of weighted hands; lines and columns start from 1
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum ParseError {
+    InvalidCard {
+        line: usize,
+        column: usize,
+        found: char,
+    },
+    EmptyHand {
+        line: usize,
+    },
+    // all hands of a list should have the same number of cards
+    HandLength {
+        line: usize,
+        expected: usize,
+        found: usize,
+    },
+    MissingBid {
+        line: usize,
+        column: usize,
+    },
+    InvalidBid {
+        line: usize,
+        column: usize,
+        found: char,
+    },
+    BidTooLarge {
+        line: usize,
+        column: usize,
+    },
+    // errors of a poker hand, given as a single string
+    MissingSuit {
+        column: usize,
+    },
+    InvalidSuit {
+        column: usize,
+        found: char,
+    },
+    DuplicateCard {
+        column: usize,
+    },
+    CardCount {
+        found: usize,
+    },
 }
 
-impl ToCard for char {
-    fn to_card(&self) -> Card {
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
-            '2' => Card::Two,
-            '3' => Card::Three,
-            '4' => Card::Four,
-            '5' => Card::Five,
-            '6' => Card::Six,
-            '7' => Card::Seven,
-            '8' => Card::Eight,
-            '9' => Card::Nine,
-            'T' => Card::T,
-            'J' => Card::J,
-            'Q' => Card::Q,
-            'K' => Card::K,
-            'A' => Card::A,
-            _ => panic!("Invalid character"),
+            ParseError::InvalidCard {
+                line,
+                column,
+                found,
+            } => write!(f, "line {line}, column {column}: invalid card {found:?}"),
+            ParseError::EmptyHand { line } => write!(f, "line {line}: no cards"),
+            ParseError::HandLength {
+                line,
+                expected,
+                found,
+            } => write!(
+                f,
+                "line {line}: hand of {found} cards, expected {expected} like the first hand"
+            ),
+            ParseError::MissingBid { line, column } => {
+                write!(f, "line {line}, column {column}: missing bid")
+            }
+            ParseError::InvalidBid {
+                line,
+                column,
+                found,
+            } => write!(
+                f,
+                "line {line}, column {column}: invalid bid character {found:?}"
+            ),
+            ParseError::BidTooLarge { line, column } => {
+                write!(f, "line {line}, column {column}: bid too large")
+            }
+            ParseError::MissingSuit { column } => write!(f, "column {column}: missing suit"),
+            ParseError::InvalidSuit { column, found } => {
+                write!(f, "column {column}: invalid suit {found:?}")
+            }
+            ParseError::DuplicateCard { column } => {
+                write!(f, "column {column}: card already in the hand")
+            }
+            ParseError::CardCount { found } => write!(f, "{found} cards, expected 5"),
+        }
+    }
+}
+
+impl TryFrom<char> for Card {
+    // the invalid char, the caller knows where it was found
+    type Error = char;
+
+    fn try_from(c: char) -> Result<Card, char> {
+        match c {
+            '2' => Ok(Card::Two),
+            '3' => Ok(Card::Three),
+            '4' => Ok(Card::Four),
+            '5' => Ok(Card::Five),
+            '6' => Ok(Card::Six),
+            '7' => Ok(Card::Seven),
+            '8' => Ok(Card::Eight),
+            '9' => Ok(Card::Nine),
+            'T' => Ok(Card::T),
+            'J' => Ok(Card::J),
+            'Q' => Ok(Card::Q),
+            'K' => Ok(Card::K),
+            'A' => Ok(Card::A),
+            _ => Err(c),
+        }
+    }
+}
+
+impl Hand {
+    // hand found at the start of the given line
+    fn parse(s: &str, line: usize) -> Result<Hand, ParseError> {
+        if s.is_empty() {
+            return Err(ParseError::EmptyHand { line });
         }
+        let cards = s
+            .chars()
+            .enumerate()
+            .map(|(i, c)| {
+                Card::try_from(c).map_err(|found| ParseError::InvalidCard {
+                    line,
+                    column: i + 1,
+                    found,
+                })
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Hand(cards))
     }
 }
 
-trait ToHand {
-    fn to_hand(&self) -> Hand;
+impl FromStr for Hand {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Hand, ParseError> {
+        Hand::parse(s, 1)
+    }
 }
 
-impl ToHand for str {
-    fn to_hand(&self) -> Hand {
-        let hand: [Card; 5] = self
+impl WeightedHand {
+    // a hand followed by whitespace and a bid, such as "32T3K 765", on the
+    // given line
+    fn parse(s: &str, line: usize) -> Result<WeightedHand, ParseError> {
+        let hand_end = s.find(char::is_whitespace).unwrap_or(s.len());
+        let hand = Hand::parse(&s[..hand_end], line)?;
+        let bid_str = s[hand_end..].trim_start();
+        let bid_start = s[..s.len() - bid_str.len()].chars().count();
+        let bid_str = bid_str.trim_end();
+        if bid_str.is_empty() {
+            return Err(ParseError::MissingBid {
+                line,
+                column: bid_start + 1,
+            });
+        }
+        if let Some((i, c)) = bid_str
             .chars()
-            .map(|c| c.to_card())
-            .collect::<Vec<Card>>()
-            .try_into()
-            .unwrap_or_else(|v: Vec<Card>| {
-                panic!("Expected a string of length {} but it was {}", 5, v.len())
+            .enumerate()
+            .find(|(_, c)| !c.is_ascii_digit())
+        {
+            return Err(ParseError::InvalidBid {
+                line,
+                column: bid_start + i + 1,
+                found: c,
             });
-        Hand(hand)
+        }
+        let weight = bid_str.parse().map_err(|_| ParseError::BidTooLarge {
+            line,
+            column: bid_start + 1,
+        })?;
+        Ok(WeightedHand { hand, weight })
+    }
+}
+
+impl FromStr for WeightedHand {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<WeightedHand, ParseError> {
+        WeightedHand::parse(s, 1)
+    }
+}
+
+// parses one weighted hand per line, reporting all malformed lines
+fn parse_hands(contents: &str) -> Result<Vec<WeightedHand>, Vec<ParseError>> {
+    let mut hands: Vec<WeightedHand> = Vec::new();
+    let mut errors = Vec::new();
+    for (i, line) in contents.lines().enumerate() {
+        match WeightedHand::parse(line, i + 1) {
+            Ok(wh) => match hands.first() {
+                Some(first) if first.hand.0.len() != wh.hand.0.len() => {
+                    errors.push(ParseError::HandLength {
+                        line: i + 1,
+                        expected: first.hand.0.len(),
+                        found: wh.hand.0.len(),
+                    })
+                }
+                _ => hands.push(wh),
+            },
+            Err(e) => errors.push(e),
+        }
+    }
+    if errors.is_empty() {
+        Ok(hands)
+    } else {
+        Err(errors)
     }
 }
 
 // SECOND PART
 
-#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 struct JokerHand(Hand);
 
-#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 struct WeightedJokerHand {
     hand: JokerHand,
     weight: usize,
 }
 
-impl Card {
-    fn to_joker_int(self) -> u8 {
-        match self {
-            Card::Two => 2,
-            Card::Three => 3,
-            Card::Four => 4,
-            Card::Five => 5,
-            Card::Six => 6,
-            Card::Seven => 7,
-            Card::Eight => 8,
-            Card::Nine => 9,
-            Card::T => 10,
-            Card::J => 1,
-            Card::Q => 12,
-            Card::K => 13,
-            Card::A => 14,
-        }
+// cards which can stand for any other card, each with the value it takes
+// when breaking ties between hands of the same type
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct WildRules(Vec<(Card, u8)>);
+
+impl WildRules {
+    // J is a joker and is now the weakest card!
+    fn jokers() -> WildRules {
+        WildRules(vec![(Card::J, 1)])
+    }
+
+    fn is_wild(&self, card: Card) -> bool {
+        self.0.iter().any(|(c, _)| *c == card)
+    }
+
+    fn tie_break_value(&self, card: Card) -> u8 {
+        self.0
+            .iter()
+            .find(|(c, _)| *c == card)
+            .map_or(card.to_int(), |(_, v)| *v)
+    }
+}
+
+impl HandRules {
+    // best category reachable when `wilds` wild cards can stand for any card,
+    // counts of the other cards being sorted in decreasing order: a category is
+    // reachable if wild cards can make up for the missing cards when the largest
+    // required counts are matched with the largest counts
+    fn classify_with_wilds(&self, counts: &[usize], wilds: usize) -> HandType {
+        self.0
+            .iter()
+            .find(|(_, pattern)| {
+                let missing: usize = pattern
+                    .iter()
+                    .enumerate()
+                    .map(|(i, p)| p.saturating_sub(counts.get(i).copied().unwrap_or(0)))
+                    .sum();
+                missing <= wilds
+            })
+            .map_or(HandType::High, |(ht, _)| *ht)
     }
 }
 
 impl JokerHand {
-    fn to_handtype(self) -> HandType {
-        let hand = self.0;
-        let mut ht = hand.to_handtype();
-        // if there are several jokers, the best hand is always reached when all jokers are
-        // replaced by the same card
-        for c in Card::iter() {
-            let mut temp_hand = hand;
-            for d in temp_hand.0.iter_mut() {
-                if *d == Card::J {
-                    *d = c;
-                }
-            }
-            if temp_hand.to_handtype() > ht {
-                ht = temp_hand.to_handtype();
-            }
+    fn to_handtype_with(&self, rules: &HandRules, wild: &WildRules) -> HandType {
+        let others: Vec<Card> = self
+            .0
+             .0
+            .iter()
+            .copied()
+            .filter(|c| !wild.is_wild(*c))
+            .collect();
+        let wilds = self.0 .0.len() - others.len();
+        rules.classify_with_wilds(&count_ranks(&others), wilds)
+    }
+
+    // card which all wild cards stand for to reach the best hand type: the most
+    // common other card (the highest one in case of a tie), or A if all cards are wild
+    fn best_substitution(&self, wild: &WildRules) -> Option<Card> {
+        if !self.0 .0.iter().any(|c| wild.is_wild(*c)) {
+            return None;
         }
-        ht
+        let others: Vec<Card> = self
+            .0
+             .0
+            .iter()
+            .copied()
+            .filter(|c| !wild.is_wild(*c))
+            .collect();
+        let best = others
+            .iter()
+            .max_by_key(|c| (others.iter().filter(|d| d == c).count(), c.to_int()));
+        Some(best.copied().unwrap_or(Card::A))
     }
-}
 
-impl Ord for JokerHand {
-    fn cmp(&self, other: &Self) -> Ordering {
+    fn sort_key_with(&self, rules: &HandRules, wild: &WildRules) -> SortKey {
+        SortKey(
+            self.to_handtype_with(rules, wild).to_int(),
+            self.0 .0.iter().map(|c| wild.tie_break_value(*c)).collect(),
+        )
+    }
+
+    fn cmp_with(&self, other: &Self, rules: &HandRules, wild: &WildRules) -> Ordering {
         if self == other {
             Ordering::Equal
         } else {
-            let ht1 = self.to_handtype();
-            let ht2 = other.to_handtype();
+            let ht1 = self.to_handtype_with(rules, wild);
+            let ht2 = other.to_handtype_with(rules, wild);
             if ht1 != ht2 {
                 ht1.to_int().cmp(&ht2.to_int())
             } else {
-                let mut i = 0;
-                while self.0 .0[i] == other.0 .0[i] {
-                    i += 1;
-                }
-                // J is now the weakest card!
-                self.0 .0[i]
-                    .to_joker_int()
-                    .cmp(&other.0 .0[i].to_joker_int())
+                // a wild card may have the value of another card, so the
+                // values are compared rather than the cards
+                let values = |h: &Self| -> Vec<u8> {
+                    h.0 .0.iter().map(|c| wild.tie_break_value(*c)).collect()
+                };
+                values(self).cmp(&values(other))
             }
         }
     }
 }
 
+impl Ord for JokerHand {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.cmp_with(other, &HandRules::camel_cards(), &WildRules::jokers())
+    }
+}
+
 impl PartialOrd for JokerHand {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
         Some(self.cmp(other))
@@ -370,23 +584,355 @@ impl PartialOrd for WeightedJokerHand {
     }
 }
 
+// COMMON RANKING
+
+// hands which can be ranked under some rule set: a hand is better than another
+// if its sort key is larger
+trait RankedHand {
+    fn hand_type(&self) -> HandType;
+    fn sort_key(&self) -> SortKey;
+}
+
+impl RankedHand for Hand {
+    fn hand_type(&self) -> HandType {
+        self.to_handtype()
+    }
+
+    fn sort_key(&self) -> SortKey {
+        self.sort_key_with(&HandRules::camel_cards())
+    }
+}
+
+impl RankedHand for JokerHand {
+    fn hand_type(&self) -> HandType {
+        self.to_handtype_with(&HandRules::camel_cards(), &WildRules::jokers())
+    }
+
+    fn sort_key(&self) -> SortKey {
+        self.sort_key_with(&HandRules::camel_cards(), &WildRules::jokers())
+    }
+}
+
+// POKER
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Suit {
+    Clubs,
+    Diamonds,
+    Hearts,
+    Spades,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+struct PokerCard {
+    rank: Card,
+    suit: Suit,
+}
+
+// five suited cards, such as "AS KD 7H 7C 2S"
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct PokerHand(Vec<PokerCard>);
+
+impl TryFrom<char> for Suit {
+    type Error = char;
+
+    fn try_from(c: char) -> Result<Suit, char> {
+        match c {
+            'C' => Ok(Suit::Clubs),
+            'D' => Ok(Suit::Diamonds),
+            'H' => Ok(Suit::Hearts),
+            'S' => Ok(Suit::Spades),
+            _ => Err(c),
+        }
+    }
+}
+
+impl FromStr for PokerHand {
+    type Err = ParseError;
+
+    // cards separated by whitespace, each a rank followed by a suit
+    fn from_str(s: &str) -> Result<PokerHand, ParseError> {
+        let mut cards: Vec<PokerCard> = Vec::new();
+        let mut chars = s.chars().enumerate().peekable();
+        while let Some((i, c)) = chars.next() {
+            if c.is_whitespace() {
+                continue;
+            }
+            let rank = Card::try_from(c).map_err(|found| ParseError::InvalidCard {
+                line: 1,
+                column: i + 1,
+                found,
+            })?;
+            let Some((j, c)) = chars.next_if(|(_, c)| !c.is_whitespace()) else {
+                return Err(ParseError::MissingSuit { column: i + 2 });
+            };
+            let suit = Suit::try_from(c).map_err(|found| ParseError::InvalidSuit {
+                column: j + 1,
+                found,
+            })?;
+            // cards are two chars long
+            if let Some((k, found)) = chars.next_if(|(_, c)| !c.is_whitespace()) {
+                return Err(ParseError::InvalidCard {
+                    line: 1,
+                    column: k + 1,
+                    found,
+                });
+            }
+            let card = PokerCard { rank, suit };
+            if cards.contains(&card) {
+                return Err(ParseError::DuplicateCard { column: i + 1 });
+            }
+            cards.push(card);
+        }
+        if cards.len() != 5 {
+            return Err(ParseError::CardCount { found: cards.len() });
+        }
+        Ok(PokerHand(cards))
+    }
+}
+
+impl PokerHand {
+    // value of the highest card of the straight, if the hand is one; the ace
+    // counts as 1 in the wheel A2345
+    fn straight_top(&self) -> Option<u8> {
+        let mut values: Vec<u8> = self.0.iter().map(|c| c.rank.to_int()).collect();
+        values.sort();
+        if values == [2, 3, 4, 5, 14] {
+            Some(5)
+        } else if values.windows(2).all(|w| w[1] == w[0] + 1) {
+            Some(values[4])
+        } else {
+            None
+        }
+    }
+
+    fn is_flush(&self) -> bool {
+        self.0.iter().all(|c| c.suit == self.0[0].suit)
+    }
+
+    // card values by decreasing number of occurrences, then decreasing value,
+    // e.g., [7, 14, 13, 2] for "AS KD 7H 7C 2S"
+    fn kickers(&self) -> Vec<u8> {
+        let mut values: Vec<u8> = self.0.iter().map(|c| c.rank.to_int()).collect();
+        values.sort_by(|a, b| b.cmp(a));
+        let mut groups: Vec<(usize, u8)> = values
+            .chunk_by(|a, b| a == b)
+            .map(|g| (g.len(), g[0]))
+            .collect();
+        groups.sort_by(|a, b| b.cmp(a));
+        groups.iter().map(|g| g.1).collect()
+    }
+}
+
+impl RankedHand for PokerHand {
+    fn hand_type(&self) -> HandType {
+        let ranks: Vec<Card> = self.0.iter().map(|c| c.rank).collect();
+        let of_a_kind = HandRules::camel_cards().classify(&count_ranks(&ranks));
+        let suited = match (self.straight_top().is_some(), self.is_flush()) {
+            (true, true) => HandType::StraightFlush,
+            (false, true) => HandType::Flush,
+            (true, false) => HandType::Straight,
+            (false, false) => HandType::High,
+        };
+        of_a_kind.max(suited)
+    }
+
+    fn sort_key(&self) -> SortKey {
+        let kickers = match self.straight_top() {
+            Some(top) => vec![top],
+            None => self.kickers(),
+        };
+        SortKey(self.hand_type().to_int(), kickers)
+    }
+}
+
+// REPORT
+
+// what makes a hand rank above the hand just below it
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum TieBreak {
+    Type,
+    // first position (starting from 1) where cards differ, for hands of the same type
+    Position(usize),
+    // both hands are equal
+    None,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct ReportLine {
+    rank: usize,
+    hand: String,
+    hand_type: HandType,
+    bid: usize,
+    substitution: Option<(Card, Card)>,
+    // None for the lowest ranked hand
+    tie_break: Option<TieBreak>,
+}
+
+// ranking of hands from worst to best, with jokers if `joker` is set
+fn report(hands: &[WeightedHand], joker: bool) -> Vec<ReportLine> {
+    let rules = HandRules::camel_cards();
+    let wild = WildRules::jokers();
+    let mut ranked: Vec<_> = hands
+        .iter()
+        .map(|wh| {
+            let jh = JokerHand(wh.hand.clone());
+            if joker {
+                let substitution = jh.best_substitution(&wild).map(|c| (Card::J, c));
+                let ht = jh.to_handtype_with(&rules, &wild);
+                (jh.sort_key_with(&rules, &wild), ht, substitution, wh)
+            } else {
+                (
+                    wh.hand.sort_key_with(&rules),
+                    wh.hand.to_handtype(),
+                    None,
+                    wh,
+                )
+            }
+        })
+        .collect();
+    ranked.sort_by(|a, b| a.0.cmp(&b.0));
+
+    let mut lines: Vec<ReportLine> = Vec::new();
+    for (i, (key, hand_type, substitution, wh)) in ranked.iter().enumerate() {
+        let tie_break = if i == 0 {
+            None
+        } else {
+            let prev = &ranked[i - 1].0;
+            Some(if prev.0 != key.0 {
+                TieBreak::Type
+            } else {
+                match key.1.iter().zip(prev.1.iter()).position(|(a, b)| a != b) {
+                    Some(pos) => TieBreak::Position(pos + 1),
+                    None => TieBreak::None,
+                }
+            })
+        };
+        lines.push(ReportLine {
+            rank: i + 1,
+            hand: wh.hand.to_string(),
+            hand_type: *hand_type,
+            bid: wh.weight,
+            substitution: *substitution,
+            tie_break,
+        });
+    }
+    lines
+}
+
+fn print_report_table(lines: &[ReportLine]) {
+    println!(
+        "{:>5}  {:<7} {:<14} {:>6}  {:<12} tie-break",
+        "rank", "hand", "type", "bid", "substitution"
+    );
+    for l in lines {
+        let substitution = match l.substitution {
+            Some((from, to)) => format!("{} -> {}", from.to_char(), to.to_char()),
+            None => "-".to_string(),
+        };
+        let tie_break = match l.tie_break {
+            Some(TieBreak::Type) => "type".to_string(),
+            Some(TieBreak::Position(p)) => format!("card {p}"),
+            Some(TieBreak::None) => "equal".to_string(),
+            None => "-".to_string(),
+        };
+        println!(
+            "{:>5}  {:<7} {:<14} {:>6}  {:<12} {}",
+            l.rank,
+            l.hand,
+            format!("{:?}", l.hand_type),
+            l.bid,
+            substitution,
+            tie_break
+        );
+    }
+}
+
+fn print_report_json(lines: &[ReportLine]) {
+    let entries: Vec<String> = lines
+        .iter()
+        .map(|l| {
+            let substitution = match l.substitution {
+                Some((from, to)) => {
+                    format!("{{\"from\": \"{}\", \"to\": \"{}\"}}", from.to_char(), to.to_char())
+                }
+                None => "null".to_string(),
+            };
+            let tie_break = match l.tie_break {
+                Some(TieBreak::Type) => "\"type\"".to_string(),
+                Some(TieBreak::Position(p)) => p.to_string(),
+                Some(TieBreak::None) => "\"equal\"".to_string(),
+                None => "null".to_string(),
+            };
+            format!(
+                "  {{\"rank\": {}, \"hand\": \"{}\", \"type\": \"{:?}\", \"bid\": {}, \"substitution\": {}, \"tie_break\": {}}}",
+                l.rank, l.hand, l.hand_type, l.bid, substitution, tie_break
+            )
+        })
+        .collect();
+    println!("[\n{}\n]", entries.join(",\n"));
+}
+
 fn main() {
     SimpleLogger::new().init().unwrap();
 
     let contents = include_str!("../input");
 
-    let mut wh_vec: Vec<_> = contents
-        .lines()
-        .map(|l| {
-            let mut it = l.split_ascii_whitespace();
-            WeightedHand {
-                hand: it.next().unwrap().to_hand(),
-                weight: it.next().unwrap().parse().unwrap(),
+    let hands = || {
+        parse_hands(contents).unwrap_or_else(|errors| {
+            for e in errors {
+                eprintln!("Invalid input: {e}");
             }
+            std::process::exit(1);
         })
-        .collect();
+    };
 
-    wh_vec.sort();
+    let args: Vec<String> = std::env::args().collect();
+    match args.get(1).map(|a| a.as_str()) {
+        // `day7 report [--joker] [--json]` explains the ranking of every hand
+        Some("report") => {
+            let hands = hands();
+            let lines = report(&hands, args.iter().any(|a| a == "--joker"));
+            if args.iter().any(|a| a == "--json") {
+                print_report_json(&lines);
+            } else {
+                print_report_table(&lines);
+            }
+            return;
+        }
+        // `day7 bench [n]` times sorting n random hands with and without sort keys
+        Some("bench") => {
+            let n = args.get(2).and_then(|a| a.parse().ok()).unwrap_or(100_000);
+            bench(n);
+            return;
+        }
+        // `day7 poker "AS KD 7H 7C 2S" ...` ranks poker hands from best to worst
+        Some("poker") => {
+            let hands: Vec<PokerHand> = args[2..]
+                .iter()
+                .map(|h| {
+                    h.parse().unwrap_or_else(|e| {
+                        eprintln!("Invalid poker hand {h:?}: {e}");
+                        std::process::exit(1);
+                    })
+                })
+                .collect();
+            let mut order: Vec<usize> = (0..hands.len()).collect();
+            order.sort_by_cached_key(|&i| Reverse(hands[i].sort_key()));
+            for (r, &i) in order.iter().enumerate() {
+                println!("{}: {} {:?}", r + 1, args[i + 2], hands[i].hand_type());
+            }
+            return;
+        }
+        _ => (),
+    }
+
+    let rules = HandRules::camel_cards();
+    let wild = WildRules::jokers();
+
+    let mut wh_vec = hands();
+
+    wh_vec.sort_by_cached_key(|wh| wh.hand.sort_key_with(&rules));
 
     debug!("{:#?}", wh_vec);
 
@@ -398,18 +944,15 @@ fn main() {
 
     println!("The total winnings is {total}");
 
-    let mut wh_vec: Vec<_> = contents
-        .lines()
-        .map(|l| {
-            let mut it = l.split_ascii_whitespace();
-            WeightedJokerHand {
-                hand: JokerHand(it.next().unwrap().to_hand()),
-                weight: it.next().unwrap().parse().unwrap(),
-            }
+    let mut wh_vec: Vec<_> = wh_vec
+        .into_iter()
+        .map(|wh| WeightedJokerHand {
+            hand: JokerHand(wh.hand),
+            weight: wh.weight,
         })
         .collect();
 
-    wh_vec.sort();
+    wh_vec.sort_by_cached_key(|wh| wh.hand.sort_key_with(&rules, &wild));
 
     debug!("{:#?}", wh_vec);
 
@@ -422,16 +965,94 @@ fn main() {
     println!("The second total winnings is {total2}");
 }
 
+// hands of 5 cards drawn with a xorshift generator
+fn random_hands(n: usize, mut seed: u64) -> Vec<Hand> {
+    let cards: Vec<Card> = Card::iter().collect();
+    (0..n)
+        .map(|_| {
+            Hand(
+                (0..5)
+                    .map(|_| {
+                        seed ^= seed << 13;
+                        seed ^= seed >> 7;
+                        seed ^= seed << 17;
+                        cards[(seed % 13) as usize]
+                    })
+                    .collect(),
+            )
+        })
+        .collect()
+}
+
+fn bench(n: usize) {
+    let rules = HandRules::camel_cards();
+    let wild = WildRules::jokers();
+    let hands = random_hands(n, 0x2023_1207);
+    println!("Sorting {n} random hands");
+
+    let mut by_cmp = hands.clone();
+    let start = Instant::now();
+    by_cmp.sort();
+    let cmp_time = start.elapsed();
+    let mut by_key = hands.clone();
+    let start = Instant::now();
+    by_key.sort_by_cached_key(|h| h.sort_key_with(&rules));
+    let key_time = start.elapsed();
+    assert_eq!(by_cmp, by_key);
+    println!("Hand:      Ord {cmp_time:?}, sort keys {key_time:?}");
+
+    let mut by_cmp: Vec<_> = hands.iter().cloned().map(JokerHand).collect();
+    let mut by_key = by_cmp.clone();
+    let start = Instant::now();
+    by_cmp.sort();
+    let cmp_time = start.elapsed();
+    let start = Instant::now();
+    by_key.sort_by_cached_key(|h| h.sort_key_with(&rules, &wild));
+    let key_time = start.elapsed();
+    assert_eq!(by_cmp, by_key);
+    println!("JokerHand: Ord {cmp_time:?}, sort keys {key_time:?}");
+}
+
 #[cfg(test)]
 mod tests {
 
     use super::*;
 
+    trait ToHand {
+        fn to_hand(&self) -> Hand;
+    }
+
+    impl ToHand for str {
+        fn to_hand(&self) -> Hand {
+            self.parse().unwrap()
+        }
+    }
+
+    // reference implementation trying all substitutions of jokers
+    fn brute_force_joker_handtype(hand: &JokerHand) -> HandType {
+        let hand = &hand.0;
+        let mut ht = hand.to_handtype();
+        // if there are several jokers, the best hand is always reached when all jokers are
+        // replaced by the same card
+        for c in Card::iter() {
+            let mut temp_hand = hand.clone();
+            for d in temp_hand.0.iter_mut() {
+                if *d == Card::J {
+                    *d = c;
+                }
+            }
+            if temp_hand.to_handtype() > ht {
+                ht = temp_hand.to_handtype();
+            }
+        }
+        ht
+    }
+
     #[test]
     fn test_to_hand() {
         assert_eq!(
             "AA2J7".to_hand(),
-            Hand([Card::A, Card::A, Card::Two, Card::J, Card::Seven])
+            Hand(vec![Card::A, Card::A, Card::Two, Card::J, Card::Seven])
         );
     }
 
@@ -446,9 +1067,305 @@ mod tests {
         assert_eq!("29944".to_hand().to_handtype(), HandType::Two);
     }
 
+    #[test]
+    fn test_to_handtype_other_sizes() {
+        assert_eq!("222".to_hand().to_handtype(), HandType::Three);
+        assert_eq!("2K2".to_hand().to_handtype(), HandType::One);
+        assert_eq!("2KA".to_hand().to_handtype(), HandType::High);
+        assert_eq!("2222333".to_hand().to_handtype(), HandType::Four);
+        assert_eq!("2233445".to_hand().to_handtype(), HandType::Two);
+        assert_eq!("22233KK".to_hand().to_handtype(), HandType::Full);
+        assert_eq!("AAAAAAA".to_hand().to_handtype(), HandType::Five);
+    }
+
+    #[test]
+    fn test_custom_rules() {
+        // three-card variant where three of a kind is the best hand
+        let rules = HandRules(vec![
+            (HandType::Five, vec![3]),
+            (HandType::One, vec![2]),
+            (HandType::High, vec![]),
+        ]);
+        assert_eq!("QQQ".to_hand().to_handtype_with(&rules), HandType::Five);
+        assert_eq!("Q5Q".to_hand().to_handtype_with(&rules), HandType::One);
+        assert_eq!("Q5K".to_hand().to_handtype_with(&rules), HandType::High);
+    }
+
     #[test]
     fn test_compare_hand() {
         assert_eq!("2244T".to_hand().cmp(&"33TTJ".to_hand()), Ordering::Less);
         assert_eq!("2244T".to_hand().cmp(&"222JJ".to_hand()), Ordering::Less);
+        // a hand which is a prefix of another one
+        let (short, long) = ("23".to_hand(), "234".to_hand());
+        assert_eq!(short.cmp(&long), Ordering::Less);
+        assert_eq!(long.cmp(&short), Ordering::Greater);
+        assert_eq!(short.sort_key().cmp(&long.sort_key()), short.cmp(&long));
+    }
+
+    #[test]
+    fn test_joker_handtype() {
+        // all hands made of 2, 3, J and Q
+        let cards = [Card::Two, Card::Three, Card::J, Card::Q];
+        for n in 0..4usize.pow(5) {
+            let hand = JokerHand(Hand((0..5).map(|i| cards[n / 4usize.pow(i) % 4]).collect()));
+            assert_eq!(
+                hand.to_handtype_with(&HandRules::camel_cards(), &WildRules::jokers()),
+                brute_force_joker_handtype(&hand),
+                "{:?}",
+                hand
+            );
+        }
+    }
+
+    #[test]
+    fn test_several_wild_ranks() {
+        let rules = HandRules::camel_cards();
+        let wild = WildRules(vec![(Card::J, 1), (Card::Two, 0)]);
+        let hand = |s: &str| JokerHand(s.to_hand());
+        assert_eq!(
+            hand("J2J2Q").to_handtype_with(&rules, &wild),
+            HandType::Five
+        );
+        assert_eq!(
+            hand("J2K3Q").to_handtype_with(&rules, &wild),
+            HandType::Three
+        );
+        assert_eq!(
+            hand("J2KKQ").to_handtype_with(&rules, &wild),
+            HandType::Four
+        );
+        assert_eq!(
+            hand("2233Q").to_handtype_with(&rules, &wild),
+            HandType::Four
+        );
+        // each wild rank has its own tie-break value
+        assert_eq!(
+            hand("2KKKQ").cmp_with(&hand("JKKKQ"), &rules, &wild),
+            Ordering::Less
+        );
+        assert_eq!(
+            hand("JKKKQ").cmp_with(&hand("3KKKK"), &rules, &wild),
+            Ordering::Less
+        );
+
+        // a wild card with the value of T ties with it, the next cards decide
+        let wild = WildRules(vec![(Card::J, 10)]);
+        let (h, g) = (hand("TJ234"), hand("JT235"));
+        assert_eq!(h.cmp_with(&g, &rules, &wild), Ordering::Less);
+        assert_eq!(
+            h.sort_key_with(&rules, &wild)
+                .cmp(&g.sort_key_with(&rules, &wild)),
+            Ordering::Less
+        );
+        assert_eq!(g.cmp_with(&h, &rules, &wild), Ordering::Greater);
+    }
+
+    #[test]
+    fn test_sort_keys() {
+        let rules = HandRules::camel_cards();
+        let wild = WildRules::jokers();
+        let hands = random_hands(500, 7);
+        for h in hands.iter() {
+            for g in hands.iter().take(20) {
+                assert_eq!(
+                    h.sort_key_with(&rules).cmp(&g.sort_key_with(&rules)),
+                    h.cmp(g)
+                );
+                let (jh, jg) = (JokerHand(h.clone()), JokerHand(g.clone()));
+                assert_eq!(
+                    jh.sort_key_with(&rules, &wild)
+                        .cmp(&jg.sort_key_with(&rules, &wild)),
+                    jh.cmp(&jg)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_camel_ranking() {
+        let mut hands: Vec<_> = ["32T3K", "T55J5", "KK677", "KTJJT", "QQQJA"]
+            .iter()
+            .map(|h| h.to_hand())
+            .collect();
+        let mut joker_hands: Vec<_> = hands.iter().cloned().map(JokerHand).collect();
+        hands.sort_by_cached_key(|h| h.sort_key());
+        assert_eq!(hands[0], "32T3K".to_hand());
+        assert_eq!(hands[4], "QQQJA".to_hand());
+        joker_hands.sort_by_cached_key(|h| h.sort_key());
+        assert_eq!(joker_hands[4], JokerHand("KTJJT".to_hand()));
+        assert_eq!(joker_hands[4].hand_type(), HandType::Four);
+    }
+
+    #[test]
+    fn test_poker_hand_type() {
+        let ht = |h: &str| h.parse::<PokerHand>().unwrap().hand_type();
+        assert_eq!(ht("AS KD 7H 7C 2S"), HandType::One);
+        assert_eq!(ht("AS AD 7H 7C 2S"), HandType::Two);
+        assert_eq!(ht("7S 7D 7H AC 2S"), HandType::Three);
+        assert_eq!(ht("3S 4D 5H 6C 7S"), HandType::Straight);
+        assert_eq!(ht("AS 2D 3H 4C 5S"), HandType::Straight);
+        assert_eq!(ht("QS KD AH 2C 3S"), HandType::High);
+        assert_eq!(ht("AH 9H 7H 4H 2H"), HandType::Flush);
+        assert_eq!(ht("7S 7D 7H AC AS"), HandType::Full);
+        assert_eq!(ht("7S 7D 7H 7C AS"), HandType::Four);
+        assert_eq!(ht("9H TH JH QH KH"), HandType::StraightFlush);
+        assert_eq!(ht("AD 2D 3D 4D 5D"), HandType::StraightFlush);
+    }
+
+    #[test]
+    fn test_poker_ranking() {
+        let key = |h: &str| h.parse::<PokerHand>().unwrap().sort_key();
+        // the wheel is the lowest straight
+        assert!(key("AS 2D 3H 4C 5S") < key("2S 3D 4H 5C 6S"));
+        assert!(key("TS JD QH KC AS") > key("9S TD JH QC KS"));
+        // flush beats straight, full house beats flush
+        assert!(key("AH 9H 7H 4H 2H") > key("TS JD QH KC AS"));
+        assert!(key("2S 2D 2H 3C 3S") > key("AH 9H 7H 4H 2H"));
+        // ties are broken by kickers rather than by position
+        assert!(key("2S 2D AH KC QS") < key("3S 3D 4H 5C 6S"));
+        assert!(key("AS AD KH QC 2S") > key("AH AC KD JH 9S"));
+        assert!(key("KS KD 3H 3C 2S") > key("QH QC JD JH AS"));
+        assert!(key("7S 7D 7H 2C 2S") > key("6H 6C 6D AH AS"));
+        assert_eq!(key("AS KD 7H 7C 2S"), key("2H 7D KH AC 7S"));
+    }
+
+    #[test]
+    fn test_best_substitution() {
+        let wild = WildRules::jokers();
+        let sub = |h: &str| JokerHand(h.to_hand()).best_substitution(&wild);
+        assert_eq!(sub("32T3K"), None);
+        assert_eq!(sub("T55J5"), Some(Card::Five));
+        assert_eq!(sub("KTJJT"), Some(Card::T));
+        assert_eq!(sub("KTJJ2"), Some(Card::K));
+        assert_eq!(sub("JJJJJ"), Some(Card::A));
+    }
+
+    #[test]
+    fn test_report() {
+        let hands: Vec<_> = [
+            ("32T3K", 765),
+            ("T55J5", 684),
+            ("KK677", 28),
+            ("KTJJT", 220),
+            ("QQQJA", 483),
+        ]
+        .iter()
+        .map(|(h, w)| WeightedHand {
+            hand: h.to_hand(),
+            weight: *w,
+        })
+        .collect();
+        let lines = report(&hands, false);
+        let order: Vec<_> = lines.iter().map(|l| l.hand.as_str()).collect();
+        assert_eq!(order, ["32T3K", "KTJJT", "KK677", "T55J5", "QQQJA"]);
+        let total: usize = lines.iter().map(|l| l.rank * l.bid).sum();
+        assert_eq!(total, 6440);
+        assert_eq!(lines[1].tie_break, Some(TieBreak::Type));
+        // KK677 and KTJJT are both two pairs, and differ at the second card
+        assert_eq!(lines[2].tie_break, Some(TieBreak::Position(2)));
+
+        let lines = report(&hands, true);
+        let order: Vec<_> = lines.iter().map(|l| l.hand.as_str()).collect();
+        assert_eq!(order, ["32T3K", "KK677", "T55J5", "QQQJA", "KTJJT"]);
+        let total: usize = lines.iter().map(|l| l.rank * l.bid).sum();
+        assert_eq!(total, 5905);
+        assert_eq!(lines[4].substitution, Some((Card::J, Card::T)));
+        assert_eq!(lines[4].hand_type, HandType::Four);
+        // T55J5 and QQQJA are both four of a kind after substitution
+        assert_eq!(lines[3].tie_break, Some(TieBreak::Position(1)));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(Card::try_from('K'), Ok(Card::K));
+        assert_eq!(
+            "2K1QA".parse::<Hand>(),
+            Err(ParseError::InvalidCard {
+                line: 1,
+                column: 3,
+                found: '1'
+            })
+        );
+        assert_eq!(
+            "32T3K 765".parse::<WeightedHand>(),
+            Ok(WeightedHand {
+                hand: "32T3K".to_hand(),
+                weight: 765
+            })
+        );
+        assert_eq!(
+            "32T3K  7x5".parse::<WeightedHand>(),
+            Err(ParseError::InvalidBid {
+                line: 1,
+                column: 9,
+                found: 'x'
+            })
+        );
+        assert_eq!(
+            "32T3K".parse::<WeightedHand>(),
+            Err(ParseError::MissingBid { line: 1, column: 6 })
+        );
+        assert_eq!(
+            "32T3K 99999999999999999999999".parse::<WeightedHand>(),
+            Err(ParseError::BidTooLarge { line: 1, column: 7 })
+        );
+        assert_eq!(
+            "AS 1S 3S 4S 5S".parse::<PokerHand>(),
+            Err(ParseError::InvalidCard {
+                line: 1,
+                column: 4,
+                found: '1'
+            })
+        );
+        assert_eq!(
+            "AS KX 3S 4S 5S".parse::<PokerHand>(),
+            Err(ParseError::InvalidSuit {
+                column: 5,
+                found: 'X'
+            })
+        );
+        assert_eq!(
+            "AS K 3S 4S 5S".parse::<PokerHand>(),
+            Err(ParseError::MissingSuit { column: 5 })
+        );
+        assert_eq!(
+            "AS KSS 3S 4S 5S".parse::<PokerHand>(),
+            Err(ParseError::InvalidCard {
+                line: 1,
+                column: 6,
+                found: 'S'
+            })
+        );
+        assert_eq!(
+            "AS 3S 3S 4S 5S".parse::<PokerHand>(),
+            Err(ParseError::DuplicateCard { column: 7 })
+        );
+        assert_eq!(
+            "AS 3S 4S 5S".parse::<PokerHand>(),
+            Err(ParseError::CardCount { found: 4 })
+        );
+    }
+
+    #[test]
+    fn test_parse_hands_reports_all_errors() {
+        let contents = "32T3K 765\nT55X5 684\n\nKK677\nKTJJT 220\nQQQJ 483\n";
+        assert_eq!(
+            parse_hands(contents),
+            Err(vec![
+                ParseError::InvalidCard {
+                    line: 2,
+                    column: 4,
+                    found: 'X'
+                },
+                ParseError::EmptyHand { line: 3 },
+                ParseError::MissingBid { line: 4, column: 6 },
+                ParseError::HandLength {
+                    line: 6,
+                    expected: 5,
+                    found: 4
+                },
+            ])
+        );
+        assert_eq!(parse_hands("32T3K 765\nKTJJT 220\n").unwrap().len(), 2);
     }
 }
@@ -1,22 +1,42 @@
 use log::debug;
 use simple_logger::SimpleLogger;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 fn main() {
     SimpleLogger::new().init().unwrap();
 
     let contents = include_str!("../input");
 
-    // build a hashmap mapping strings "XYZ" to pairs of strings ("RST", "UVW")
-    let mut hm: HashMap<&str, (&str, &str)> = HashMap::new();
-    for line in contents.lines().skip(2) {
-        let v: Vec<_> = line.split_ascii_whitespace().collect();
-        hm.insert(v[0], (&v[2][1..4], &v[3][0..3]));
-    }
+    let (lrseq, hm) = parse_network(contents);
     debug!("{:?}", hm);
 
-    // retrieve LR sequence
-    let lrseq: Vec<_> = contents.lines().next().unwrap().chars().collect();
+    let nodes = starting_nodes(contents);
+    debug!("=== STARTING NODES ===: {:?}", nodes);
+
+    let args: Vec<String> = std::env::args().collect();
+    match args.get(1).map(|a| a.as_str()) {
+        // `day8 analyze` checks whether the LCM of cycle lengths gives the answer
+        Some("analyze") => {
+            let cycles: Vec<Cycle> = nodes.iter().map(|n| find_cycle(&hm, &lrseq, n)).collect();
+            analyze(&nodes, &cycles);
+            return;
+        }
+        // `day8 export dot|json [--scc]` prints the network
+        Some("export") => {
+            let collapse = args.iter().any(|a| a == "--scc");
+            match args.get(2).map(|a| a.as_str()) {
+                Some("dot") => print!("{}", to_dot(&hm, &nodes, collapse)),
+                Some("json") => println!("{}", to_json(&hm, &nodes, collapse)),
+                _ => {
+                    eprintln!("Usage: day8 export dot|json [--scc]");
+                    std::process::exit(1);
+                }
+            }
+            return;
+        }
+        _ => (),
+    }
+
     let seqlen = lrseq.len();
 
     debug!("LR seq length: {seqlen}");
@@ -35,68 +55,625 @@ fn main() {
 
     println!("The number of steps is {ctr}");
 
-    let nodes: Vec<&str> = contents
+    let cycles: Vec<Cycle> = nodes.iter().map(|n| find_cycle(&hm, &lrseq, n)).collect();
+    debug!("{:#?}", cycles);
+
+    match ghost_steps(&cycles) {
+        Some(steps) => println!("The number of steps for ghosts is {steps}"),
+        None => println!("The ghosts never all end up on nodes ending with Z"),
+    }
+}
+
+// returns the LR sequence and a hashmap mapping strings "XYZ" to pairs of strings ("RST", "UVW")
+fn parse_network(contents: &str) -> (Vec<char>, HashMap<&str, (&str, &str)>) {
+    let lrseq: Vec<_> = contents.lines().next().unwrap().chars().collect();
+    let mut hm: HashMap<&str, (&str, &str)> = HashMap::new();
+    for line in contents.lines().skip(2) {
+        let v: Vec<_> = line.split_ascii_whitespace().collect();
+        hm.insert(v[0], (&v[2][1..4], &v[3][0..3]));
+    }
+    (lrseq, hm)
+}
+
+// nodes ending with A, in input order
+fn starting_nodes(contents: &str) -> Vec<&str> {
+    contents
         .lines()
         .skip(2)
         .filter(|l| l.chars().nth(2).unwrap() == 'A')
         .map(|l| &l[0..3])
-        .collect();
-    debug!("=== STARTING NODES ===: {:?}", nodes);
+        .collect()
+}
+
+// Walking from a starting node, the state (node, position in the LR sequence)
+// eventually repeats: the walk is a tail of `tail` steps followed by a cycle
+// of `length` steps. Z nodes can be hit in the tail and/or in the cycle.
+#[derive(Debug)]
+struct Cycle {
+    // number of steps before entering the cycle
+    tail: usize,
+    // number of steps of the cycle
+    length: usize,
+    // steps (counted from the start) at which a Z node is hit before the cycle
+    tail_z: Vec<usize>,
+    // steps (counted from the start) at which a Z node is hit during the first
+    // round of the cycle, i.e., in tail..tail + length
+    cycle_z: Vec<usize>,
+}
+
+impl Cycle {
+    // whether the walk is on a Z node after `steps` steps
+    fn hits_z(&self, steps: usize) -> bool {
+        if steps < self.tail {
+            self.tail_z.contains(&steps)
+        } else {
+            let offset = self.tail + (steps - self.tail) % self.length;
+            self.cycle_z.contains(&offset)
+        }
+    }
+
+    // reason why this walk breaks the assumption that it reaches a single Z node
+    // after exactly `length` steps and then comes back to it every `length` steps
+    fn lcm_shortcut_issue(&self) -> Option<String> {
+        if !self.tail_z.is_empty() {
+            Some(format!(
+                "Z node hit before the cycle at steps {:?}",
+                self.tail_z
+            ))
+        } else if self.cycle_z.len() != 1 {
+            Some(format!(
+                "{} Z nodes hit in the cycle instead of 1",
+                self.cycle_z.len()
+            ))
+        } else if self.cycle_z[0] != self.length {
+            Some(format!(
+                "Z node first reached after {} steps but cycle length is {}",
+                self.cycle_z[0], self.length
+            ))
+        } else {
+            None
+        }
+    }
+}
+
+// prints, for each starting node, the structure of its walk, and the LCM of
+// cycle lengths if it is the answer; exits with an error otherwise
+fn analyze(nodes: &[&str], cycles: &[Cycle]) {
+    println!(
+        "{:<6} {:>8} {:>8} {:<24} LCM shortcut",
+        "start", "tail", "cycle", "Z offsets"
+    );
+    let mut issues = Vec::new();
+    for (node, c) in nodes.iter().zip(cycles) {
+        let z_offsets: Vec<_> = c.tail_z.iter().chain(c.cycle_z.iter()).collect();
+        let issue = c.lcm_shortcut_issue();
+        println!(
+            "{:<6} {:>8} {:>8} {:<24} {}",
+            node,
+            c.tail,
+            c.length,
+            format!("{:?}", z_offsets),
+            if issue.is_none() { "valid" } else { "invalid" }
+        );
+        if let Some(issue) = issue {
+            issues.push(format!("{node}: {issue}"));
+        }
+    }
+
+    if issues.is_empty() {
+        let steps = cycles.iter().fold(1, |acc, c| lcm(acc, c.length as u128));
+        println!("The LCM shortcut is valid, the number of steps for ghosts is {steps}");
+    } else {
+        eprintln!("The LCM shortcut is not valid for this input:");
+        for issue in issues {
+            eprintln!("  {issue}");
+        }
+        std::process::exit(1);
+    }
+}
+
+fn find_cycle(hm: &HashMap<&str, (&str, &str)>, lrseq: &[char], start: &str) -> Cycle {
+    let seqlen = lrseq.len();
+    // step at which each state (node, index in LR sequence) was first seen
+    let mut seen: HashMap<(&str, usize), usize> = HashMap::new();
+    let mut z_steps = Vec::new();
+    let mut node = start;
+    let mut ctr = 0;
+    loop {
+        if let Some(&first) = seen.get(&(node, ctr % seqlen)) {
+            let (tail_z, cycle_z) = z_steps.iter().partition(|&&s| s < first);
+            return Cycle {
+                tail: first,
+                length: ctr - first,
+                tail_z,
+                cycle_z,
+            };
+        }
+        seen.insert((node, ctr % seqlen), ctr);
+        if ends_with_z(node) {
+            z_steps.push(ctr);
+        }
+        let (lnode, rnode) = hm.get(node).unwrap();
+        if lrseq[ctr % seqlen] == 'L' {
+            node = lnode;
+        } else {
+            node = rnode;
+        }
+        ctr += 1;
+    }
+}
+
+// smallest number of steps after which all walks are simultaneously on a Z node
+fn ghost_steps(cycles: &[Cycle]) -> Option<u128> {
+    // a solution smaller than the longest tail is a tail hit of some walk
+    let mut candidates: Vec<usize> = cycles.iter().flat_map(|c| c.tail_z.clone()).collect();
+    candidates.sort();
+    if let Some(&steps) = candidates
+        .iter()
+        .find(|&&s| cycles.iter().all(|c| c.hits_z(s)))
+    {
+        return Some(steps as u128);
+    }
+
+    // otherwise, each walk hits Z nodes at steps z + k * length for z in cycle_z,
+    // and we intersect these arithmetic progressions with the generalized CRT
+    let max_tail = cycles.iter().map(|c| c.tail).max()? as u128;
+    let mut progressions: Vec<(u128, u128)> = vec![(0, 1)];
+    for c in cycles {
+        let mut next = Vec::new();
+        for &(r, m) in progressions.iter() {
+            for &z in c.cycle_z.iter() {
+                if let Some(p) = crt(r, m, z as u128, c.length as u128) {
+                    if !next.contains(&p) {
+                        next.push(p);
+                    }
+                }
+            }
+        }
+        progressions = next;
+    }
+    debug!("Solutions (residue, modulus): {:?}", progressions);
 
-    let mut cycle_lengths = Vec::new();
-
-    for n in nodes.iter() {
-        let mut node = n;
-        debug!("===STARTING NODE: {node}");
-        ctr = 0;
-        // find first node and next move
-        debug!("--- First trip ---");
-        loop {
-            let (lnode, rnode) = hm.get(node).unwrap();
-            if lrseq[ctr % seqlen] == 'L' {
-                node = lnode;
+    // smallest solution in each progression which is not smaller than all tails
+    progressions
+        .iter()
+        .map(|&(r, m)| {
+            if r >= max_tail {
+                r
             } else {
-                node = rnode;
+                r + (max_tail - r).div_ceil(m) * m
             }
-            ctr += 1;
-            if ends_with_z(node) {
-                let first_visit = (node, lrseq[ctr % seqlen]);
-                debug!("First node/move reached: {:?}", first_visit);
-                debug!("Number of steps: {ctr}");
-                break;
+        })
+        .min()
+}
+
+// EXPORT
+
+// strongly connected components of the network (Tarjan's algorithm);
+// returns the components and the index of the component of each node
+fn sccs<'a>(
+    hm: &HashMap<&'a str, (&'a str, &'a str)>,
+) -> (Vec<Vec<&'a str>>, HashMap<&'a str, usize>) {
+    struct Tarjan<'a, 'b> {
+        hm: &'b HashMap<&'a str, (&'a str, &'a str)>,
+        index: HashMap<&'a str, usize>,
+        lowlink: HashMap<&'a str, usize>,
+        stack: Vec<&'a str>,
+        on_stack: HashSet<&'a str>,
+        components: Vec<Vec<&'a str>>,
+    }
+
+    impl<'a> Tarjan<'a, '_> {
+        fn visit(&mut self, node: &'a str) {
+            let idx = self.index.len();
+            self.index.insert(node, idx);
+            self.lowlink.insert(node, idx);
+            self.stack.push(node);
+            self.on_stack.insert(node);
+            let (lnode, rnode) = self.hm[node];
+            for next in [lnode, rnode] {
+                if !self.index.contains_key(next) {
+                    self.visit(next);
+                    let low = self.lowlink[node].min(self.lowlink[next]);
+                    self.lowlink.insert(node, low);
+                } else if self.on_stack.contains(next) {
+                    let low = self.lowlink[node].min(self.index[next]);
+                    self.lowlink.insert(node, low);
+                }
+            }
+            if self.lowlink[node] == self.index[node] {
+                let mut component = Vec::new();
+                loop {
+                    let n = self.stack.pop().unwrap();
+                    self.on_stack.remove(n);
+                    component.push(n);
+                    if n == node {
+                        break;
+                    }
+                }
+                component.sort();
+                self.components.push(component);
             }
         }
-        let tail = ctr;
-        // find next node
-        debug!("--- Next trip ---");
-        loop {
-            let (lnode, rnode) = hm.get(node).unwrap();
-            if lrseq[ctr % seqlen] == 'L' {
-                node = lnode;
-            } else {
-                node = rnode;
+    }
+
+    let mut tarjan = Tarjan {
+        hm,
+        index: HashMap::new(),
+        lowlink: HashMap::new(),
+        stack: Vec::new(),
+        on_stack: HashSet::new(),
+        components: Vec::new(),
+    };
+    let mut keys: Vec<_> = hm.keys().copied().collect();
+    keys.sort();
+    for node in keys {
+        if !tarjan.index.contains_key(node) {
+            tarjan.visit(node);
+        }
+    }
+    let mut component_of = HashMap::new();
+    for (i, component) in tarjan.components.iter().enumerate() {
+        for node in component.iter() {
+            component_of.insert(*node, i);
+        }
+    }
+    (tarjan.components, component_of)
+}
+
+// components reachable from a starting node, sorted
+fn reachable_components(
+    hm: &HashMap<&str, (&str, &str)>,
+    component_of: &HashMap<&str, usize>,
+    start: &str,
+) -> Vec<usize> {
+    let mut visited = HashSet::from([start]);
+    let mut queue = vec![start];
+    while let Some(node) = queue.pop() {
+        let (lnode, rnode) = hm[node];
+        for next in [lnode, rnode] {
+            if visited.insert(next) {
+                queue.push(next);
+            }
+        }
+    }
+    let mut components: Vec<_> = visited.iter().map(|n| component_of[n]).collect();
+    components.sort();
+    components.dedup();
+    components
+}
+
+// edges between components with their L/R labels, sorted
+fn component_edges(
+    hm: &HashMap<&str, (&str, &str)>,
+    components: &[Vec<&str>],
+    component_of: &HashMap<&str, usize>,
+    kept: &HashSet<usize>,
+) -> Vec<(usize, usize, String)> {
+    let mut edges: Vec<(usize, usize, String)> = Vec::new();
+    for (i, component) in components.iter().enumerate() {
+        if !kept.contains(&i) {
+            continue;
+        }
+        for node in component.iter() {
+            let (lnode, rnode) = hm[node];
+            for (next, dir) in [(lnode, "L"), (rnode, "R")] {
+                let j = component_of[next];
+                if j == i {
+                    continue;
+                }
+                match edges.iter_mut().find(|e| e.0 == i && e.1 == j) {
+                    Some(e) if !e.2.contains(dir) => e.2 = "LR".to_string(),
+                    Some(_) => (),
+                    None => edges.push((i, j, dir.to_string())),
+                }
             }
-            ctr += 1;
-            if ends_with_z(node) {
-                debug!("Next node/move reached: {:?}", (node, lrseq[ctr % seqlen]));
-                debug!("Number of steps: {}", ctr);
-                break;
+        }
+    }
+    edges.sort();
+    edges
+}
+
+// network in Graphviz DOT format, with starting nodes in green and nodes ending
+// with Z in red; if `collapse` is set, each strongly connected component
+// reachable from a starting node is drawn as a single node
+fn to_dot(hm: &HashMap<&str, (&str, &str)>, starts: &[&str], collapse: bool) -> String {
+    let mut dot = String::from("digraph network {\n");
+    if collapse {
+        let (components, component_of) = sccs(hm);
+        let kept: HashSet<usize> = starts
+            .iter()
+            .flat_map(|s| reachable_components(hm, &component_of, s))
+            .collect();
+        let mut kept_sorted: Vec<_> = kept.iter().copied().collect();
+        kept_sorted.sort();
+        for i in kept_sorted {
+            let component = &components[i];
+            let z_nodes: Vec<_> = component.iter().filter(|n| ends_with_z(n)).collect();
+            let label = if component.len() == 1 {
+                component[0].to_string()
+            } else {
+                format!("SCC {i}\\n{} nodes\\nZ: {:?}", component.len(), z_nodes).replace('"', "")
+            };
+            let color = if component.iter().any(|n| starts.contains(n)) {
+                ", style=filled, fillcolor=green"
+            } else if !z_nodes.is_empty() {
+                ", style=filled, fillcolor=red"
+            } else {
+                ""
+            };
+            dot += &format!("  c{i} [label=\"{label}\"{color}];\n");
+        }
+        for (i, j, dir) in component_edges(hm, &components, &component_of, &kept) {
+            dot += &format!("  c{i} -> c{j} [label=\"{dir}\"];\n");
+        }
+    } else {
+        let mut keys: Vec<_> = hm.keys().copied().collect();
+        keys.sort();
+        for node in keys.iter() {
+            if starts.contains(node) {
+                dot += &format!("  \"{node}\" [style=filled, fillcolor=green];\n");
+            } else if ends_with_z(node) {
+                dot += &format!("  \"{node}\" [style=filled, fillcolor=red];\n");
             }
         }
-        cycle_lengths.push(tail);
+        for node in keys.iter() {
+            let (lnode, rnode) = hm[node];
+            // node names may start with a digit, which DOT only accepts quoted
+            dot += &format!("  \"{node}\" -> \"{lnode}\" [label=\"L\"];\n");
+            dot += &format!("  \"{node}\" -> \"{rnode}\" [label=\"R\"];\n");
+        }
+    }
+    dot += "}\n";
+    dot
+}
+
+// network as a JSON adjacency list; if `collapse` is set, the strongly connected
+// components reachable from each starting node and the edges between them instead
+fn to_json(hm: &HashMap<&str, (&str, &str)>, starts: &[&str], collapse: bool) -> String {
+    if collapse {
+        let (components, component_of) = sccs(hm);
+        let reachable: Vec<_> = starts
+            .iter()
+            .map(|s| (s, reachable_components(hm, &component_of, s)))
+            .collect();
+        let kept: HashSet<usize> = reachable.iter().flat_map(|r| r.1.clone()).collect();
+        let mut kept_sorted: Vec<_> = kept.iter().copied().collect();
+        kept_sorted.sort();
+        let comps: Vec<_> = kept_sorted
+            .iter()
+            .map(|&i| {
+                let nodes: Vec<_> = components[i].iter().map(|n| format!("\"{n}\"")).collect();
+                format!("    \"{i}\": [{}]", nodes.join(", "))
+            })
+            .collect();
+        let edges: Vec<_> = component_edges(hm, &components, &component_of, &kept)
+            .iter()
+            .map(|(i, j, dir)| format!("    {{\"from\": {i}, \"to\": {j}, \"label\": \"{dir}\"}}"))
+            .collect();
+        let reach: Vec<_> = reachable
+            .iter()
+            .map(|(s, r)| format!("    \"{s}\": {:?}", r))
+            .collect();
+        format!(
+            "{{\n  \"components\": {{\n{}\n  }},\n  \"edges\": [\n{}\n  ],\n  \"reachable\": {{\n{}\n  }}\n}}",
+            comps.join(",\n"),
+            edges.join(",\n"),
+            reach.join(",\n")
+        )
+    } else {
+        let mut keys: Vec<_> = hm.keys().copied().collect();
+        keys.sort();
+        let entries: Vec<_> = keys
+            .iter()
+            .map(|node| {
+                let (lnode, rnode) = hm[node];
+                format!("  \"{node}\": {{\"L\": \"{lnode}\", \"R\": \"{rnode}\"}}")
+            })
+            .collect();
+        format!("{{\n{}\n}}", entries.join(",\n"))
+    }
+}
+
+fn gcd(a: u128, b: u128) -> u128 {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
     }
+}
+
+fn lcm(a: u128, b: u128) -> u128 {
+    a / gcd(a, b) * b
+}
 
-    // Each starting node ends up on a unique node ending
-    // with Z, and then it is on a cycle with the same number of steps
-    // e.g. "AAA" -- 19637 steps --> "ZZZ" -- 19637 steps --> "ZZZ"
-    // which makes things much simpler to solve
+// returns (x, y, g) such that a * x + b * y = g = gcd(a, b)
+fn extended_gcd(a: i128, b: i128) -> (i128, i128, i128) {
+    if b == 0 {
+        (1, 0, a)
+    } else {
+        let (x, y, g) = extended_gcd(b, a % b);
+        (y, x - (a / b) * y, g)
+    }
+}
 
-    println!("{:?}", cycle_lengths);
-    // [19637, 12643, 14257, 15871, 11567, 19099]
-    // finished with Sagemath :)
-    // answer: 8811050362409
+// solves x = r1 mod m1, x = r2 mod m2 for moduli which are not necessarily coprime;
+// returns (r, lcm(m1, m2)) with 0 <= r < lcm(m1, m2), or None if there is no solution
+fn crt(r1: u128, m1: u128, r2: u128, m2: u128) -> Option<(u128, u128)> {
+    let (r1, r2) = (r1 % m1, r2 % m2);
+    let g = gcd(m1, m2);
+    if r1 % g != r2 % g {
+        return None;
+    }
+    let m = lcm(m1, m2);
+    // x = r1 + m1 * k with m1 * k = r2 - r1 mod m2, i.e., (m1/g) * k = (r2 - r1)/g mod (m2/g)
+    let (m1g, m2g) = ((m1 / g) as i128, (m2 / g) as i128);
+    let (inv, _, _) = extended_gcd(m1g % m2g, m2g);
+    let diff = (r2 as i128 - r1 as i128) / g as i128;
+    let k = (diff % m2g * (inv % m2g)).rem_euclid(m2g) as u128;
+    Some(((r1 + m1 * k) % m, m))
 }
 
 fn ends_with_z(node: &str) -> bool {
     node.chars().nth(2).unwrap() == 'Z'
 }
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    fn ghost_steps_of(contents: &str) -> Option<u128> {
+        let (lrseq, hm) = parse_network(contents);
+        let cycles: Vec<Cycle> = starting_nodes(contents)
+            .iter()
+            .map(|n| find_cycle(&hm, &lrseq, n))
+            .collect();
+        ghost_steps(&cycles)
+    }
+
+    #[test]
+    fn test_crt() {
+        assert_eq!(crt(2, 3, 3, 5), Some((8, 15)));
+        assert_eq!(crt(1, 4, 3, 6), Some((9, 12)));
+        assert_eq!(crt(0, 4, 1, 6), None);
+        assert_eq!(crt(5, 7, 5, 7), Some((5, 7)));
+    }
+
+    #[test]
+    fn test_lcm_shortcut_issue() {
+        let valid = Cycle {
+            tail: 3,
+            length: 5,
+            tail_z: vec![],
+            cycle_z: vec![5],
+        };
+        assert_eq!(valid.lcm_shortcut_issue(), None);
+        let offset = Cycle {
+            tail: 1,
+            length: 3,
+            tail_z: vec![],
+            cycle_z: vec![1],
+        };
+        assert!(offset.lcm_shortcut_issue().is_some());
+        let several = Cycle {
+            tail: 0,
+            length: 4,
+            tail_z: vec![],
+            cycle_z: vec![2, 3],
+        };
+        assert!(several.lcm_shortcut_issue().is_some());
+    }
+
+    #[test]
+    fn test_sccs() {
+        let contents = "L\n\n\
+                        11A = (11B, 11B)\n\
+                        11B = (11Z, 11C)\n\
+                        11Z = (11B, 11B)\n\
+                        11C = (11C, 11C)";
+        let (_, hm) = parse_network(contents);
+        let (components, component_of) = sccs(&hm);
+        assert_eq!(components.len(), 3);
+        assert_eq!(component_of["11B"], component_of["11Z"]);
+        assert_ne!(component_of["11A"], component_of["11B"]);
+        assert_ne!(component_of["11C"], component_of["11B"]);
+        assert_eq!(reachable_components(&hm, &component_of, "11A").len(), 3);
+        assert_eq!(reachable_components(&hm, &component_of, "11C").len(), 1);
+    }
+
+    #[test]
+    fn test_ghost_steps_example() {
+        let contents = "LR\n\n\
+                        11A = (11B, XXX)\n\
+                        11B = (XXX, 11Z)\n\
+                        11Z = (11B, XXX)\n\
+                        22A = (22B, XXX)\n\
+                        22B = (22C, 22C)\n\
+                        22C = (22Z, 22Z)\n\
+                        22Z = (22B, 22B)\n\
+                        XXX = (XXX, XXX)";
+        assert_eq!(ghost_steps_of(contents), Some(6));
+    }
+
+    #[test]
+    fn test_ghost_steps_offset_and_several_z() {
+        // 11A reaches 11Z after 1 step then every 3 steps (tail != cycle length),
+        // 22A hits Z nodes at steps 2 and 3 of each 4-step cycle
+        let contents = "L\n\n\
+                        11A = (11Z, 11A)\n\
+                        11Z = (11B, 11B)\n\
+                        11B = (11C, 11C)\n\
+                        11C = (11Z, 11Z)\n\
+                        22A = (22B, 22B)\n\
+                        22B = (22Z, 22Z)\n\
+                        22Z = (23Z, 23Z)\n\
+                        23Z = (22A, 22A)";
+        // 11A: 1, 4, 7, 10, ... ; 22A: 2, 3, 6, 7, 10, 11, ...
+        assert_eq!(ghost_steps_of(contents), Some(7));
+    }
+
+    #[test]
+    fn test_ghost_steps_tail_hit() {
+        // both walks are on a Z node after 1 step, before entering their cycles
+        let contents = "L\n\n\
+                        11A = (11Z, 11Z)\n\
+                        11Z = (11B, 11B)\n\
+                        11B = (11B, 11B)\n\
+                        22A = (22Z, 22Z)\n\
+                        22Z = (22B, 22B)\n\
+                        22B = (22C, 22C)\n\
+                        22C = (22B, 22B)";
+        assert_eq!(ghost_steps_of(contents), Some(1));
+    }
+
+    #[test]
+    fn test_ghost_steps_no_solution() {
+        // 11A is on a Z node at odd steps, 22A at even steps
+        let contents = "L\n\n\
+                        11A = (11Z, 11Z)\n\
+                        11Z = (11A, 11A)\n\
+                        22A = (22B, 22B)\n\
+                        22B = (22Z, 22Z)\n\
+                        22Z = (22B, 22B)";
+        assert_eq!(ghost_steps_of(contents), None);
+    }
+
+    #[test]
+    fn test_export() {
+        let contents = "L\n\n\
+                        11A = (11Z, 11B)\n\
+                        11B = (11B, 11B)\n\
+                        11Z = (11A, 11A)";
+        let (_, hm) = parse_network(contents);
+        let starts = starting_nodes(contents);
+        assert_eq!(
+            to_dot(&hm, &starts, false),
+            "digraph network {\n\
+             \x20 \"11A\" [style=filled, fillcolor=green];\n\
+             \x20 \"11Z\" [style=filled, fillcolor=red];\n\
+             \x20 \"11A\" -> \"11Z\" [label=\"L\"];\n\
+             \x20 \"11A\" -> \"11B\" [label=\"R\"];\n\
+             \x20 \"11B\" -> \"11B\" [label=\"L\"];\n\
+             \x20 \"11B\" -> \"11B\" [label=\"R\"];\n\
+             \x20 \"11Z\" -> \"11A\" [label=\"L\"];\n\
+             \x20 \"11Z\" -> \"11A\" [label=\"R\"];\n\
+             }\n"
+        );
+        assert_eq!(
+            to_json(&hm, &starts, false),
+            "{\n\
+             \x20 \"11A\": {\"L\": \"11Z\", \"R\": \"11B\"},\n\
+             \x20 \"11B\": {\"L\": \"11B\", \"R\": \"11B\"},\n\
+             \x20 \"11Z\": {\"L\": \"11A\", \"R\": \"11A\"}\n\
+             }"
+        );
+
+        // 11A and 11Z form a component, from which the 11B one is reachable
+        let dot = to_dot(&hm, &starts, true);
+        assert_eq!(dot.matches(" -> ").count(), 1);
+        let json = to_json(&hm, &starts, true);
+        assert!(json.contains("[\"11A\", \"11Z\"]") || json.contains("[\"11Z\", \"11A\"]"));
+        assert!(json.contains("[\"11B\"]"));
+    }
+}